@@ -21,12 +21,19 @@ Register-ArgumentCompleter -Native -CommandName 'dn' -ScriptBlock {
 
     $completions = @(switch ($command) {
         'dn' {
-            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
-            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             [CompletionResult]::new('-V', '-V ', [CompletionResultType]::ParameterName, 'Print version')
             [CompletionResult]::new('--version', '--version', [CompletionResultType]::ParameterName, 'Print version')
             [CompletionResult]::new('new', 'new', [CompletionResultType]::ParameterValue, 'Create a new note')
             [CompletionResult]::new('rename', 'rename', [CompletionResultType]::ParameterValue, 'Rename an existing note')
+            [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List existing notes')
+            [CompletionResult]::new('link', 'link', [CompletionResultType]::ParameterValue, 'Print a link to a note for use in another note')
+            [CompletionResult]::new('backlinks', 'backlinks', [CompletionResultType]::ParameterValue, 'List the notes which link to a note')
+            [CompletionResult]::new('check-links', 'check-links', [CompletionResultType]::ParameterValue, 'Report links to notes which do not exist')
+            [CompletionResult]::new('templates', 'templates', [CompletionResultType]::ParameterValue, 'Manage the templates available to new notes')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
         }
@@ -35,8 +42,11 @@ Register-ArgumentCompleter -Native -CommandName 'dn' -ScriptBlock {
             [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which the note will be created')
             [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
             [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
-            [CompletionResult]::new('-T', '-T ', [CompletionResultType]::ParameterName, 'Template file to add contents to new note')
-            [CompletionResult]::new('--template', '--template', [CompletionResultType]::ParameterName, 'Template file to add contents to new note')
+            [CompletionResult]::new('-T', '-T ', [CompletionResultType]::ParameterName, 'Template name or file to add contents to new note')
+            [CompletionResult]::new('--template', '--template', [CompletionResultType]::ParameterName, 'Template name or file to add contents to new note')
+            [CompletionResult]::new('-S', '-S ', [CompletionResultType]::ParameterName, 'Value for a field declared by the template')
+            [CompletionResult]::new('--set', '--set', [CompletionResultType]::ParameterName, 'Value for a field declared by the template')
+            [CompletionResult]::new('--title-from', '--title-from', [CompletionResultType]::ParameterName, 'Take the title from the body read from standard input when none is given')
             [CompletionResult]::new('-s', '-s', [CompletionResultType]::ParameterName, 'Signature for the note')
             [CompletionResult]::new('--signature', '--signature', [CompletionResultType]::ParameterName, 'Signature for the note')
             [CompletionResult]::new('-t', '-t', [CompletionResultType]::ParameterName, 'Title for the note')
@@ -45,13 +55,28 @@ Register-ArgumentCompleter -Native -CommandName 'dn' -ScriptBlock {
             [CompletionResult]::new('--extension', '--extension', [CompletionResultType]::ParameterName, 'File extension for the note')
             [CompletionResult]::new('-k', '-k', [CompletionResultType]::ParameterName, 'Keywords for the note')
             [CompletionResult]::new('--keywords', '--keywords', [CompletionResultType]::ParameterName, 'Keywords for the note')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
             [CompletionResult]::new('-p', '-p', [CompletionResultType]::ParameterName, 'Print the absolute path of the created note')
             [CompletionResult]::new('--print', '--print', [CompletionResultType]::ParameterName, 'Print the absolute path of the created note')
-            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
-            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('-f', '-f', [CompletionResultType]::ParameterName, 'Overwrite any existing file at the output path')
+            [CompletionResult]::new('--force', '--force', [CompletionResultType]::ParameterName, 'Overwrite any existing file at the output path')
+            [CompletionResult]::new('-D', '-D ', [CompletionResultType]::ParameterName, 'Print the planned operations without touching the filesystem')
+            [CompletionResult]::new('--dry-run', '--dry-run', [CompletionResultType]::ParameterName, 'Print the planned operations without touching the filesystem')
+            [CompletionResult]::new('-i', '-i', [CompletionResultType]::ParameterName, 'Prompt for the title, keywords, signature and extension')
+            [CompletionResult]::new('--interactive', '--interactive', [CompletionResultType]::ParameterName, 'Prompt for the title, keywords, signature and extension')
+            [CompletionResult]::new('-E', '-E ', [CompletionResultType]::ParameterName, 'Open the note in the configured editor afterwards')
+            [CompletionResult]::new('--edit', '--edit', [CompletionResultType]::ParameterName, 'Open the note in the configured editor afterwards')
+            [CompletionResult]::new('--stdin', '--stdin', [CompletionResultType]::ParameterName, 'Read the body of the note from standard input')
+            [CompletionResult]::new('-N', '-N ', [CompletionResultType]::ParameterName, 'Do not generate front matter for the note')
+            [CompletionResult]::new('--no-frontmatter', '--no-frontmatter', [CompletionResultType]::ParameterName, 'Do not generate front matter for the note')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             break
         }
         'dn;rename' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Directory in which to rewrite links to the note')
+            [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which to rewrite links to the note')
             [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
             [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
             [CompletionResult]::new('-s', '-s', [CompletionResultType]::ParameterName, 'New signature for the note')
@@ -66,17 +91,127 @@ Register-ArgumentCompleter -Native -CommandName 'dn' -ScriptBlock {
             [CompletionResult]::new('--remove-keywords', '--remove-keywords', [CompletionResultType]::ParameterName, 'Remove keywords from the current or new keywords')
             [CompletionResult]::new('-e', '-e', [CompletionResultType]::ParameterName, 'New file extension for the note')
             [CompletionResult]::new('--extension', '--extension', [CompletionResultType]::ParameterName, 'New file extension for the note')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
             [CompletionResult]::new('-p', '-p', [CompletionResultType]::ParameterName, 'Print the absolute path of the created file')
             [CompletionResult]::new('--print', '--print', [CompletionResultType]::ParameterName, 'Print the absolute path of the created file')
+            [CompletionResult]::new('-f', '-f', [CompletionResultType]::ParameterName, 'Overwrite any existing file at the output path')
+            [CompletionResult]::new('--force', '--force', [CompletionResultType]::ParameterName, 'Overwrite any existing file at the output path')
+            [CompletionResult]::new('-D', '-D ', [CompletionResultType]::ParameterName, 'Print the planned operations without touching the filesystem')
+            [CompletionResult]::new('--dry-run', '--dry-run', [CompletionResultType]::ParameterName, 'Print the planned operations without touching the filesystem')
+            [CompletionResult]::new('-i', '-i', [CompletionResultType]::ParameterName, 'Prompt for the title, keywords, signature and extension')
+            [CompletionResult]::new('--interactive', '--interactive', [CompletionResultType]::ParameterName, 'Prompt for the title, keywords, signature and extension')
+            [CompletionResult]::new('-E', '-E ', [CompletionResultType]::ParameterName, 'Open the note in the configured editor afterwards')
+            [CompletionResult]::new('--edit', '--edit', [CompletionResultType]::ParameterName, 'Open the note in the configured editor afterwards')
             [CompletionResult]::new('-I', '-I ', [CompletionResultType]::ParameterName, 'Generate an identifier even if there is an existing one')
             [CompletionResult]::new('--regenerate-identifier', '--regenerate-identifier', [CompletionResultType]::ParameterName, 'Generate an identifier even if there is an existing one')
-            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
-            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('-L', '-L ', [CompletionResultType]::ParameterName, 'Rewrite links to the note across the notes directory')
+            [CompletionResult]::new('--update-links', '--update-links', [CompletionResultType]::ParameterName, 'Rewrite links to the note across the notes directory')
+            [CompletionResult]::new('-N', '-N ', [CompletionResultType]::ParameterName, 'Leave the content of the note untouched instead of updating its front matter')
+            [CompletionResult]::new('--no-frontmatter', '--no-frontmatter', [CompletionResultType]::ParameterName, 'Leave the content of the note untouched instead of updating its front matter')
+            [CompletionResult]::new('-F', '-F ', [CompletionResultType]::ParameterName, 'Take the note''s metadata from its front matter rather than its file name')
+            [CompletionResult]::new('--from-frontmatter', '--from-frontmatter', [CompletionResultType]::ParameterName, 'Take the note''s metadata from its front matter rather than its file name')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;list' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Directory in which to search for notes')
+            [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which to search for notes')
+            [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('-s', '-s', [CompletionResultType]::ParameterName, 'Only list notes with this signature')
+            [CompletionResult]::new('--signature', '--signature', [CompletionResultType]::ParameterName, 'Only list notes with this signature')
+            [CompletionResult]::new('-t', '-t', [CompletionResultType]::ParameterName, 'Only list notes whose title contains this text')
+            [CompletionResult]::new('--title', '--title', [CompletionResultType]::ParameterName, 'Only list notes whose title contains this text')
+            [CompletionResult]::new('-k', '-k', [CompletionResultType]::ParameterName, 'Only list notes with all of these keywords')
+            [CompletionResult]::new('--keywords', '--keywords', [CompletionResultType]::ParameterName, 'Only list notes with all of these keywords')
+            [CompletionResult]::new('-f', '-f', [CompletionResultType]::ParameterName, 'Only list notes created on or after this date (YYYY-MM-DD or identifier)')
+            [CompletionResult]::new('--from', '--from', [CompletionResultType]::ParameterName, 'Only list notes created on or after this date (YYYY-MM-DD or identifier)')
+            [CompletionResult]::new('-u', '-u', [CompletionResultType]::ParameterName, 'Only list notes created on or before this date (YYYY-MM-DD or identifier)')
+            [CompletionResult]::new('--to', '--to', [CompletionResultType]::ParameterName, 'Only list notes created on or before this date (YYYY-MM-DD or identifier)')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-n', '-n', [CompletionResultType]::ParameterName, 'List the files which do not follow the dn naming scheme instead of notes')
+            [CompletionResult]::new('--non-conforming', '--non-conforming', [CompletionResultType]::ParameterName, 'List the files which do not follow the dn naming scheme instead of notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;link' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Directory in which to search for the note by identifier')
+            [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which to search for the note by identifier')
+            [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('-e', '-e', [CompletionResultType]::ParameterName, 'File extension of the note the link will be placed in')
+            [CompletionResult]::new('--extension', '--extension', [CompletionResultType]::ParameterName, 'File extension of the note the link will be placed in')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-w', '-w', [CompletionResultType]::ParameterName, 'Include the title of the note as the link''s description')
+            [CompletionResult]::new('--with-title', '--with-title', [CompletionResultType]::ParameterName, 'Include the title of the note as the link''s description')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;backlinks' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Directory in which to search for notes')
+            [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which to search for notes')
+            [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;check-links' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Directory in which to check notes')
+            [CompletionResult]::new('--directory', '--directory', [CompletionResultType]::ParameterName, 'Directory in which to check notes')
+            [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;templates' {
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List the templates which can be referred to by name')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'dn;templates;list' {
+            [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Configuration file path')
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('--format', '--format', [CompletionResultType]::ParameterName, 'Format in which to print the affected notes')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'dn;templates;help' {
+            [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List the templates which can be referred to by name')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'dn;templates;help;list' {
+            break
+        }
+        'dn;templates;help;help' {
             break
         }
         'dn;help' {
             [CompletionResult]::new('new', 'new', [CompletionResultType]::ParameterValue, 'Create a new note')
             [CompletionResult]::new('rename', 'rename', [CompletionResultType]::ParameterValue, 'Rename an existing note')
+            [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List existing notes')
+            [CompletionResult]::new('link', 'link', [CompletionResultType]::ParameterValue, 'Print a link to a note for use in another note')
+            [CompletionResult]::new('backlinks', 'backlinks', [CompletionResultType]::ParameterValue, 'List the notes which link to a note')
+            [CompletionResult]::new('check-links', 'check-links', [CompletionResultType]::ParameterValue, 'Report links to notes which do not exist')
+            [CompletionResult]::new('templates', 'templates', [CompletionResultType]::ParameterValue, 'Manage the templates available to new notes')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
         }
@@ -86,6 +221,25 @@ Register-ArgumentCompleter -Native -CommandName 'dn' -ScriptBlock {
         'dn;help;rename' {
             break
         }
+        'dn;help;list' {
+            break
+        }
+        'dn;help;link' {
+            break
+        }
+        'dn;help;backlinks' {
+            break
+        }
+        'dn;help;check-links' {
+            break
+        }
+        'dn;help;templates' {
+            [CompletionResult]::new('list', 'list', [CompletionResultType]::ParameterValue, 'List the templates which can be referred to by name')
+            break
+        }
+        'dn;help;templates;list' {
+            break
+        }
         'dn;help;help' {
             break
         }
@@ -1,6 +1,6 @@
 # Print an optspec for argparse to handle cmd's options that are independent of any subcommand.
 function __fish_dn_global_optspecs
-	string join \n h/help V/version
+	string join \n o/format= h/help V/version
 end
 
 function __fish_dn_needs_command
@@ -24,20 +24,41 @@ function __fish_dn_using_subcommand
 	contains -- $cmd[1] $argv
 end
 
-complete -c dn -n "__fish_dn_needs_command" -s h -l help -d 'Print help'
+complete -c dn -n "__fish_dn_needs_command" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_needs_command" -s h -l help -d 'Print help (see more with \'--help\')'
 complete -c dn -n "__fish_dn_needs_command" -s V -l version -d 'Print version'
 complete -c dn -n "__fish_dn_needs_command" -f -a "new" -d 'Create a new note'
 complete -c dn -n "__fish_dn_needs_command" -f -a "rename" -d 'Rename an existing note'
+complete -c dn -n "__fish_dn_needs_command" -f -a "list" -d 'List existing notes'
+complete -c dn -n "__fish_dn_needs_command" -f -a "link" -d 'Print a link to a note for use in another note'
+complete -c dn -n "__fish_dn_needs_command" -f -a "backlinks" -d 'List the notes which link to a note'
+complete -c dn -n "__fish_dn_needs_command" -f -a "check-links" -d 'Report links to notes which do not exist'
+complete -c dn -n "__fish_dn_needs_command" -f -a "templates" -d 'Manage the templates available to new notes'
 complete -c dn -n "__fish_dn_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
 complete -c dn -n "__fish_dn_using_subcommand new" -s d -l directory -d 'Directory in which the note will be created' -r
 complete -c dn -n "__fish_dn_using_subcommand new" -s c -l config -d 'Configuration file path' -r
-complete -c dn -n "__fish_dn_using_subcommand new" -s T -l template -d 'Template file to add contents to new note' -r
+complete -c dn -n "__fish_dn_using_subcommand new" -s T -l template -d 'Template name or file to add contents to new note' -r
+complete -c dn -n "__fish_dn_using_subcommand new" -s S -l set -d 'Value for a field declared by the template' -r
+complete -c dn -n "__fish_dn_using_subcommand new" -l title-from -d 'Take the title from the body read from standard input when none is given' -r -f -a "line\t'The first line which is not blank'
+heading\t'The first Markdown or Org heading'"
 complete -c dn -n "__fish_dn_using_subcommand new" -s s -l signature -d 'Signature for the note' -r
 complete -c dn -n "__fish_dn_using_subcommand new" -s t -l title -d 'Title for the note' -r
 complete -c dn -n "__fish_dn_using_subcommand new" -s e -l extension -d 'File extension for the note' -r
 complete -c dn -n "__fish_dn_using_subcommand new" -s k -l keywords -d 'Keywords for the note' -r
+complete -c dn -n "__fish_dn_using_subcommand new" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
 complete -c dn -n "__fish_dn_using_subcommand new" -s p -l print -d 'Print the absolute path of the created note'
-complete -c dn -n "__fish_dn_using_subcommand new" -s h -l help -d 'Print help'
+complete -c dn -n "__fish_dn_using_subcommand new" -s f -l force -d 'Overwrite any existing file at the output path'
+complete -c dn -n "__fish_dn_using_subcommand new" -s D -l dry-run -d 'Print the planned operations without touching the filesystem'
+complete -c dn -n "__fish_dn_using_subcommand new" -s i -l interactive -d 'Prompt for the title, keywords, signature and extension'
+complete -c dn -n "__fish_dn_using_subcommand new" -s E -l edit -d 'Open the note in the configured editor afterwards'
+complete -c dn -n "__fish_dn_using_subcommand new" -l stdin -d 'Read the body of the note from standard input'
+complete -c dn -n "__fish_dn_using_subcommand new" -s N -l no-frontmatter -d 'Do not generate front matter for the note'
+complete -c dn -n "__fish_dn_using_subcommand new" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s d -l directory -d 'Directory in which to rewrite links to the note' -r
 complete -c dn -n "__fish_dn_using_subcommand rename" -s c -l config -d 'Configuration file path' -r
 complete -c dn -n "__fish_dn_using_subcommand rename" -s s -l signature -d 'New signature for the note' -r
 complete -c dn -n "__fish_dn_using_subcommand rename" -s t -l title -d 'New title for the note' -r
@@ -45,9 +66,70 @@ complete -c dn -n "__fish_dn_using_subcommand rename" -s k -l keywords -d 'New k
 complete -c dn -n "__fish_dn_using_subcommand rename" -s A -l add-keywords -d 'Add keywords to the current or new keywords' -r
 complete -c dn -n "__fish_dn_using_subcommand rename" -s R -l remove-keywords -d 'Remove keywords from the current or new keywords' -r
 complete -c dn -n "__fish_dn_using_subcommand rename" -s e -l extension -d 'New file extension for the note' -r
+complete -c dn -n "__fish_dn_using_subcommand rename" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
 complete -c dn -n "__fish_dn_using_subcommand rename" -s p -l print -d 'Print the absolute path of the created file'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s f -l force -d 'Overwrite any existing file at the output path'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s D -l dry-run -d 'Print the planned operations without touching the filesystem'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s i -l interactive -d 'Prompt for the title, keywords, signature and extension'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s E -l edit -d 'Open the note in the configured editor afterwards'
 complete -c dn -n "__fish_dn_using_subcommand rename" -s I -l regenerate-identifier -d 'Generate an identifier even if there is an existing one'
-complete -c dn -n "__fish_dn_using_subcommand rename" -s h -l help -d 'Print help'
-complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename help" -f -a "new" -d 'Create a new note'
-complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename help" -f -a "rename" -d 'Rename an existing note'
-complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s L -l update-links -d 'Rewrite links to the note across the notes directory'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s N -l no-frontmatter -d 'Leave the content of the note untouched instead of updating its front matter'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s F -l from-frontmatter -d 'Take the note\'s metadata from its front matter rather than its file name'
+complete -c dn -n "__fish_dn_using_subcommand rename" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand list" -s d -l directory -d 'Directory in which to search for notes' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s c -l config -d 'Configuration file path' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s s -l signature -d 'Only list notes with this signature' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s t -l title -d 'Only list notes whose title contains this text' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s k -l keywords -d 'Only list notes with all of these keywords' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s f -l from -d 'Only list notes created on or after this date (YYYY-MM-DD or identifier)' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s u -l to -d 'Only list notes created on or before this date (YYYY-MM-DD or identifier)' -r
+complete -c dn -n "__fish_dn_using_subcommand list" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand list" -s n -l non-conforming -d 'List the files which do not follow the dn naming scheme instead of notes'
+complete -c dn -n "__fish_dn_using_subcommand list" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand link" -s d -l directory -d 'Directory in which to search for the note by identifier' -r
+complete -c dn -n "__fish_dn_using_subcommand link" -s c -l config -d 'Configuration file path' -r
+complete -c dn -n "__fish_dn_using_subcommand link" -s e -l extension -d 'File extension of the note the link will be placed in' -r
+complete -c dn -n "__fish_dn_using_subcommand link" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand link" -s w -l with-title -d 'Include the title of the note as the link\'s description'
+complete -c dn -n "__fish_dn_using_subcommand link" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand backlinks" -s d -l directory -d 'Directory in which to search for notes' -r
+complete -c dn -n "__fish_dn_using_subcommand backlinks" -s c -l config -d 'Configuration file path' -r
+complete -c dn -n "__fish_dn_using_subcommand backlinks" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand backlinks" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand check-links" -s d -l directory -d 'Directory in which to check notes' -r
+complete -c dn -n "__fish_dn_using_subcommand check-links" -s c -l config -d 'Configuration file path' -r
+complete -c dn -n "__fish_dn_using_subcommand check-links" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand check-links" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand templates; and not __fish_seen_subcommand_from list help" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand templates; and not __fish_seen_subcommand_from list help" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand templates; and not __fish_seen_subcommand_from list help" -f -a "list" -d 'List the templates which can be referred to by name'
+complete -c dn -n "__fish_dn_using_subcommand templates; and not __fish_seen_subcommand_from list help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c dn -n "__fish_dn_using_subcommand templates; and __fish_seen_subcommand_from list" -s c -l config -d 'Configuration file path' -r
+complete -c dn -n "__fish_dn_using_subcommand templates; and __fish_seen_subcommand_from list" -s o -l format -d 'Format in which to print the affected notes' -r -f -a "text\t'Plain file paths'
+json\t'One JSON object per note'
+tsv\t'One line of tab-separated values per note'"
+complete -c dn -n "__fish_dn_using_subcommand templates; and __fish_seen_subcommand_from list" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c dn -n "__fish_dn_using_subcommand templates; and __fish_seen_subcommand_from help" -f -a "list" -d 'List the templates which can be referred to by name'
+complete -c dn -n "__fish_dn_using_subcommand templates; and __fish_seen_subcommand_from help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "new" -d 'Create a new note'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "rename" -d 'Rename an existing note'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "list" -d 'List existing notes'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "link" -d 'Print a link to a note for use in another note'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "backlinks" -d 'List the notes which link to a note'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "check-links" -d 'Report links to notes which do not exist'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "templates" -d 'Manage the templates available to new notes'
+complete -c dn -n "__fish_dn_using_subcommand help; and not __fish_seen_subcommand_from new rename list link backlinks check-links templates help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c dn -n "__fish_dn_using_subcommand help; and __fish_seen_subcommand_from templates" -f -a "list" -d 'List the templates which can be referred to by name'
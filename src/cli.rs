@@ -5,7 +5,7 @@
 
 //! CLAP-derive struct definition specifying the command line interface for dn.
 
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(
@@ -16,6 +16,38 @@
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Format in which to print the affected notes
+    #[arg(
+        short = 'o',
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        global = true,
+        action = ArgAction::Set,
+    )]
+    pub cli_format: OutputFormat,
+}
+
+/// The formats in which dn can report the notes a command has affected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Plain file paths
+    Text,
+    /// One JSON object per note
+    Json,
+    /// One line of tab-separated values per note
+    Tsv,
+}
+
+/// The parts of a note's body from which its title can be taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TitleSource {
+    /// The first line which is not blank
+    Line,
+    /// The first Markdown or Org heading
+    Heading,
 }
 
 #[derive(Subcommand)]
@@ -30,6 +62,38 @@ pub enum Commands {
         )]
         cli_print: bool,
 
+        /// Overwrite any existing file at the output path
+        #[arg(
+            short = 'f',
+            long = "force",
+            action = ArgAction::SetTrue,
+        )]
+        cli_force: bool,
+
+        /// Print the planned operations without touching the filesystem
+        #[arg(
+            short = 'D',
+            long = "dry-run",
+            action = ArgAction::SetTrue,
+        )]
+        cli_dry_run: bool,
+
+        /// Prompt for the title, keywords, signature and extension
+        #[arg(
+            short = 'i',
+            long = "interactive",
+            action = ArgAction::SetTrue,
+        )]
+        cli_interactive: bool,
+
+        /// Open the note in the configured editor afterwards
+        #[arg(
+            short = 'E',
+            long = "edit",
+            action = ArgAction::SetTrue,
+        )]
+        cli_edit: bool,
+
         /// Directory in which the note will be created
         #[arg(
             short = 'd',
@@ -48,15 +112,48 @@ pub enum Commands {
         )]
         cli_config_path: Option<String>,
 
-        /// Template file to add contents to new note
+        /// Template name or file to add contents to new note
         #[arg(
             short = 'T',
             long = "template",
-            value_name = "PATH",
+            value_name = "TEMPLATE",
             action = ArgAction::Set,
         )]
         cli_template_path: Option<String>,
 
+        /// Value for a field declared by the template
+        #[arg(
+            short = 'S',
+            long = "set",
+            value_name = "FIELD=VALUE",
+            action = ArgAction::Append,
+        )]
+        cli_fields: Vec<String>,
+
+        /// Read the body of the note from standard input
+        #[arg(
+            long = "stdin",
+            action = ArgAction::SetTrue,
+        )]
+        cli_stdin: bool,
+
+        /// Take the title from the body read from standard input when none is given
+        #[arg(
+            long = "title-from",
+            value_name = "SOURCE",
+            requires = "cli_stdin",
+            action = ArgAction::Set,
+        )]
+        cli_title_from: Option<TitleSource>,
+
+        /// Do not generate front matter for the note
+        #[arg(
+            short = 'N',
+            long = "no-frontmatter",
+            action = ArgAction::SetTrue,
+        )]
+        cli_no_frontmatter: bool,
+
         /// Signature for the note
         #[arg(
             short = 's',
@@ -107,6 +204,38 @@ pub enum Commands {
         )]
         cli_print: bool,
 
+        /// Overwrite any existing file at the output path
+        #[arg(
+            short = 'f',
+            long = "force",
+            action = ArgAction::SetTrue,
+        )]
+        cli_force: bool,
+
+        /// Print the planned operations without touching the filesystem
+        #[arg(
+            short = 'D',
+            long = "dry-run",
+            action = ArgAction::SetTrue,
+        )]
+        cli_dry_run: bool,
+
+        /// Prompt for the title, keywords, signature and extension
+        #[arg(
+            short = 'i',
+            long = "interactive",
+            action = ArgAction::SetTrue,
+        )]
+        cli_interactive: bool,
+
+        /// Open the note in the configured editor afterwards
+        #[arg(
+            short = 'E',
+            long = "edit",
+            action = ArgAction::SetTrue,
+        )]
+        cli_edit: bool,
+
         /// Generate an identifier even if there is an existing one
         #[arg(
             short = 'I',
@@ -115,6 +244,40 @@ pub enum Commands {
         )]
         cli_regenerate_identifier: bool,
 
+        /// Rewrite links to the note across the notes directory
+        #[arg(
+            short = 'L',
+            long = "update-links",
+            action = ArgAction::SetTrue,
+        )]
+        cli_update_links: bool,
+
+        /// Directory in which to rewrite links to the note
+        #[arg(
+            short = 'd',
+            long = "directory",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_directory_path: Option<String>,
+
+        /// Leave the content of the note untouched instead of updating its front matter
+        #[arg(
+            short = 'N',
+            long = "no-frontmatter",
+            action = ArgAction::SetTrue,
+        )]
+        cli_no_frontmatter: bool,
+
+        /// Take the note's metadata from its front matter rather than its file name
+        #[arg(
+            short = 'F',
+            long = "from-frontmatter",
+            alias = "from-front-matter",
+            action = ArgAction::SetTrue,
+        )]
+        cli_from_frontmatter: bool,
+
         /// Configuration file path
         #[arg(
             short = 'c',
@@ -178,4 +341,188 @@ pub enum Commands {
         )]
         cli_extension: Option<String>,
     },
+
+    /// List existing notes
+    List {
+        /// Directory in which to search for notes
+        #[arg(
+            short = 'd',
+            long = "directory",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_directory_path: Option<String>,
+
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_config_path: Option<String>,
+
+        /// Only list notes with this signature
+        #[arg(
+            short = 's',
+            long = "signature",
+            value_name = "SIGNATURE",
+            action = ArgAction::Set,
+        )]
+        cli_signature: Option<String>,
+
+        /// Only list notes whose title contains this text
+        #[arg(
+            short = 't',
+            long = "title",
+            value_name = "TITLE",
+            action = ArgAction::Set,
+        )]
+        cli_title: Option<String>,
+
+        /// Only list notes with all of these keywords
+        #[arg(
+            short = 'k',
+            long = "keywords",
+            value_name = "KEYWORDS",
+            action = ArgAction::Set,
+        )]
+        cli_keywords: Option<String>,
+
+        /// Only list notes created on or after this date (YYYY-MM-DD or identifier)
+        #[arg(
+            short = 'f',
+            long = "from",
+            value_name = "DATE",
+            action = ArgAction::Set,
+        )]
+        cli_from: Option<String>,
+
+        /// Only list notes created on or before this date (YYYY-MM-DD or identifier)
+        #[arg(
+            short = 'u',
+            long = "to",
+            value_name = "DATE",
+            action = ArgAction::Set,
+        )]
+        cli_to: Option<String>,
+
+        /// List the files which do not follow the dn naming scheme instead of notes
+        #[arg(
+            short = 'n',
+            long = "non-conforming",
+            action = ArgAction::SetTrue,
+            conflicts_with_all = ["cli_signature", "cli_title", "cli_keywords", "cli_from", "cli_to"],
+        )]
+        cli_non_conforming: bool,
+    },
+
+    /// Print a link to a note for use in another note
+    Link {
+        /// Path or identifier of the note to link to
+        #[arg(value_name = "NOTE")]
+        target: String,
+
+        /// Directory in which to search for the note by identifier
+        #[arg(
+            short = 'd',
+            long = "directory",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_directory_path: Option<String>,
+
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_config_path: Option<String>,
+
+        /// File extension of the note the link will be placed in
+        #[arg(
+            short = 'e',
+            long = "extension",
+            value_name = "EXTENSION",
+            action = ArgAction::Set,
+        )]
+        cli_extension: Option<String>,
+
+        /// Include the title of the note as the link's description
+        #[arg(
+            short = 'w',
+            long = "with-title",
+            action = ArgAction::SetTrue,
+        )]
+        cli_with_title: bool,
+    },
+
+    /// List the notes which link to a note
+    Backlinks {
+        /// Path or identifier of the linked note
+        #[arg(value_name = "NOTE")]
+        target: String,
+
+        /// Directory in which to search for notes
+        #[arg(
+            short = 'd',
+            long = "directory",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_directory_path: Option<String>,
+
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_config_path: Option<String>,
+    },
+
+    /// Report links to notes which do not exist
+    CheckLinks {
+        /// Directory in which to check notes
+        #[arg(
+            short = 'd',
+            long = "directory",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_directory_path: Option<String>,
+
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_config_path: Option<String>,
+    },
+
+    /// Manage the templates available to new notes
+    Templates {
+        #[command(subcommand)]
+        command: TemplatesCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TemplatesCommands {
+    /// List the templates which can be referred to by name
+    List {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            value_name = "PATH",
+            action = ArgAction::Set,
+        )]
+        cli_config_path: Option<String>,
+    },
 }
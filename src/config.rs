@@ -7,7 +7,7 @@
 //! and the configuration of individual modules.
 
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     env, fs,
     path::{Path, PathBuf},
 };
@@ -18,20 +18,32 @@
 use crate::{
     directory::{environment_config_dir, environment_notes_dir},
     metadata::SEGMENT_SEPARATORS,
+    template::TemplateField,
 };
 
 /// A `mut self` builder that allows progressively updating an input state for a new `Config`.
 #[derive(Debug, Default)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "Each flag overrides an independent configuration option"
+)]
 pub struct ConfigBuilder {
     base_config: Option<Config>,
     file_directory: Option<String>,
     file_default_extension: Option<String>,
     file_regenerate_identifier: bool,
     file_template_path: Option<PathBuf>,
+    frontmatter_disabled: bool,
+    interactive: bool,
+    update_links: bool,
 }
 
 /// The configuration values for the file name, directory, template, and general metadata.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "Each flag is an independent option in the configuration file"
+)]
 pub struct Config {
     /// The directory in which notes will be created.
     #[serde(default = "default_notes_directory")]
@@ -49,13 +61,127 @@ pub struct Config {
     #[serde(default = "r#false")]
     pub regenerate_identifier: bool,
 
-    /// A file path to the template file, the contents of which will be inserted in the new note.
+    /// A file path to, or the name of, the template file, the contents of which will be inserted
+    /// in the new note.
     #[serde(default = "none")]
     pub template_path: Option<PathBuf>,
 
+    /// A directory containing templates which can be referred to by their file names.
+    #[serde(default = "none")]
+    pub template_directory: Option<PathBuf>,
+
+    /// Names which can be used in place of template file paths, mapped to those templates.
+    #[serde(default = "BTreeMap::new")]
+    pub templates: BTreeMap<String, TemplateEntry>,
+
+    /// Rules which select a template for a new note from its metadata when none is provided on
+    /// the command line, in order of priority.
+    #[serde(default = "Vec::new")]
+    pub template_rules: Vec<TemplateRule>,
+
+    /// The `chrono` format string used for the `{{date}}` template variable.
+    #[serde(default = "default_template_date_format")]
+    pub template_date_format: String,
+
     /// Characters to be sanitised out of the file metadata.
     #[serde(default = "default_illegal_characters")]
     pub illegal_characters: HashSet<char>,
+
+    /// Whether or not to generate front matter at the top of new notes.
+    #[serde(default = "r#true")]
+    pub frontmatter_enabled: bool,
+
+    /// The front matter syntax to use for Markdown notes.
+    #[serde(default = "default_markdown_frontmatter_format")]
+    pub markdown_frontmatter_format: MarkdownFrontmatterFormat,
+
+    /// Whether or not to prompt for missing metadata when creating or renaming notes.
+    #[serde(default = "r#false")]
+    pub interactive: bool,
+
+    /// Whether or not to rewrite links to a note across the notes directory when it is renamed.
+    #[serde(default = "r#false")]
+    pub update_links: bool,
+
+    /// The command used to open notes for editing, in place of the `VISUAL` and `EDITOR`
+    /// environment variables.
+    #[serde(default = "none")]
+    pub editor: Option<String>,
+
+    /// Whether or not the editor accepts a `+line` argument to place the cursor on a line.
+    #[serde(default = "r#false")]
+    pub editor_line_argument: bool,
+}
+
+/// An entry in the `templates` table, which is either the path to a template file or a table
+/// describing the template.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TemplateEntry {
+    /// The path to a template file.
+    Path(PathBuf),
+    /// A template file and how it should be applied.
+    Detailed {
+        /// The path to the template file.
+        path: PathBuf,
+        /// Whether the template is a program whose output becomes the note's content.
+        #[serde(default = "r#false")]
+        executable: bool,
+        /// The fields which must be filled in before the template is applied, asked for before
+        /// any declared in the template's header.
+        #[serde(default = "Vec::new")]
+        fields: Vec<TemplateField>,
+    },
+}
+
+impl TemplateEntry {
+    /// Returns the path to the template file.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Path(path) | Self::Detailed { path, .. } => path,
+        }
+    }
+
+    /// Returns whether the template is a program whose output becomes the note's content.
+    pub fn is_executable(&self) -> bool {
+        match self {
+            Self::Path(_) => false,
+            Self::Detailed { executable, .. } => *executable,
+        }
+    }
+
+    /// Returns the fields declared for the template in the configuration file.
+    pub fn fields(&self) -> &[TemplateField] {
+        match self {
+            Self::Path(_) => &[],
+            Self::Detailed { fields, .. } => fields,
+        }
+    }
+}
+
+/// A rule which selects a template for new notes matching all of its criteria.
+///
+/// Criteria which are absent match every note.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateRule {
+    /// The template to select, as a name or a file path.
+    pub template: PathBuf,
+
+    /// Keywords which must all be present on the note.
+    #[serde(default = "none")]
+    pub keywords: Option<Vec<String>>,
+
+    /// The extension the note must have.
+    #[serde(default = "none")]
+    pub extension: Option<String>,
+
+    /// The signature the note must have.
+    #[serde(default = "none")]
+    pub signature: Option<String>,
+
+    /// The directory in which, or beneath which, the note must be created.
+    #[serde(default = "none")]
+    pub directory: Option<PathBuf>,
 }
 
 /// The segments which comprise a dn file name.
@@ -74,6 +200,16 @@ pub enum FilenameSegment {
     Extension,
 }
 
+/// The front matter syntaxes available for Markdown notes.
+#[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub enum MarkdownFrontmatterFormat {
+    #[default]
+    #[serde(alias = "yaml")]
+    Yaml,
+    #[serde(alias = "toml")]
+    Toml,
+}
+
 impl Config {
     /// Creates a new builder initialised with default values.
     pub fn builder() -> ConfigBuilder {
@@ -112,6 +248,24 @@ pub fn with_file_template_path(mut self, value: PathBuf) -> Self {
         self
     }
 
+    /// Sets whether or not to disable front matter generation and synchronisation on the builder.
+    pub fn with_frontmatter_disabled(mut self, value: bool) -> Self {
+        self.frontmatter_disabled = value;
+        self
+    }
+
+    /// Sets whether or not to prompt for missing metadata on the builder.
+    pub fn with_interactive(mut self, value: bool) -> Self {
+        self.interactive = value;
+        self
+    }
+
+    /// Sets whether or not to rewrite links to a renamed note on the builder.
+    pub fn with_update_links(mut self, value: bool) -> Self {
+        self.update_links = value;
+        self
+    }
+
     /// Builds the final `Config` state, falling back to the base configuration file
     /// values where no builder value has been specified.
     ///
@@ -157,6 +311,24 @@ pub fn build(&self) -> Config {
             .or(base_config.template_path.as_ref())
             .cloned();
 
+        let frontmatter_enabled = if self.frontmatter_disabled {
+            false
+        } else {
+            base_config.frontmatter_enabled
+        };
+
+        let interactive = if self.interactive {
+            true
+        } else {
+            base_config.interactive
+        };
+
+        let update_links = if self.update_links {
+            true
+        } else {
+            base_config.update_links
+        };
+
         // NOTE: It is essential that @=-_. are ALWAYS in the illegal characters,
         // even when overwritten by users.
         let illegal_characters = base_config
@@ -171,6 +343,9 @@ pub fn build(&self) -> Config {
             regenerate_identifier,
             template_path,
             illegal_characters,
+            frontmatter_enabled,
+            interactive,
+            update_links,
             ..base_config
         }
     }
@@ -184,7 +359,17 @@ fn default() -> Self {
             default_extension: default_file_extension(),
             regenerate_identifier: r#false(),
             template_path: none::<PathBuf>(),
+            template_directory: none::<PathBuf>(),
+            templates: BTreeMap::new(),
+            template_rules: Vec::new(),
+            template_date_format: default_template_date_format(),
             illegal_characters: default_illegal_characters(),
+            frontmatter_enabled: r#true(),
+            markdown_frontmatter_format: default_markdown_frontmatter_format(),
+            interactive: r#false(),
+            update_links: r#false(),
+            editor: none::<String>(),
+            editor_line_argument: r#false(),
         }
     }
 }
@@ -300,6 +485,17 @@ fn default_file_extension() -> String {
     "txt".to_owned()
 }
 
+/// Returns the default value for the template date format in `Config`. For use in serde macros.
+///
+/// # Value
+///
+/// ```rust
+/// "%Y-%m-%d".to_owned()
+/// ```
+fn default_template_date_format() -> String {
+    "%Y-%m-%d".to_owned()
+}
+
 /// Returns the default value for illegal characters in `Config`. For use in serde macros.
 ///
 /// # Value
@@ -317,6 +513,23 @@ fn default_illegal_characters() -> HashSet<char> {
     ])
 }
 
+/// Returns the default value for the Markdown front matter format in `Config`. For use in serde
+/// macros.
+///
+/// # Value
+///
+/// ```rust
+/// MarkdownFrontmatterFormat::Yaml
+/// ```
+fn default_markdown_frontmatter_format() -> MarkdownFrontmatterFormat {
+    MarkdownFrontmatterFormat::Yaml
+}
+
+/// Returns `true`. For use in serde macros.
+fn r#true() -> bool {
+    true
+}
+
 /// Returns `false`. For use in serde macros.
 fn r#false() -> bool {
     false
@@ -385,13 +598,19 @@ fn builder_builds_with_supplied_values() {
             .with_file_default_extension(default_extension.clone())
             .with_file_directory(directory.clone())
             .with_file_regenerate_identifier(regenerate_identifier)
-            .with_file_template_path(template_path.clone().into());
+            .with_file_template_path(template_path.clone().into())
+            .with_frontmatter_disabled(true)
+            .with_interactive(true)
+            .with_update_links(true);
 
         let expected = Config {
             directory: directory.into(),
             default_extension,
             regenerate_identifier,
             template_path: Some(template_path.into()),
+            frontmatter_enabled: false,
+            interactive: true,
+            update_links: true,
             ..Default::default()
         };
 
@@ -6,11 +6,26 @@
 //! Utilities for accessing and/or creating necessary directories.
 
 use std::{
-    env, fs,
+    collections::{BTreeSet, HashSet},
+    env,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
     path::{Path, PathBuf},
+    sync::LazyLock,
 };
 
 use anyhow::{anyhow, Error};
+use regex::Regex;
+
+use crate::filename::{parse_identifier, parse_keywords};
+
+/// The character which stands for the user's home directory at the start of a path.
+const HOME_INDICATOR: &str = "~";
+
+/// Regex to match an environment variable reference, as either `$NAME` or `${NAME}`.
+static REGEX_ENVIRONMENT_VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$(?:\{(\w+)\}|(\w+))").expect("Invalid environment variable regex pattern")
+});
 
 /// Retrieves the path to the "notes" directory inside the user's "Documents" directory.
 ///
@@ -83,44 +98,433 @@ pub fn environment_config_dir() -> Result<PathBuf, Error> {
     }
 }
 
+/// Expands a leading home indicator, "~", and any `$VAR` or `${VAR}` environment variables in a
+/// path.
+///
+/// # Errors
+///
+/// This function will return an error if the path is not valid UTF-8, or if the home directory or
+/// any referenced environment variable cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let path = expand_path("~/notes/${TEMPLATE_NAME}.txt")?;
+/// assert_eq!(path, PathBuf::from("/home/me/notes/journal.txt"));
+/// ```
+pub fn expand_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, Error> {
+    let path_ref = path.as_ref();
+    let value = path_ref
+        .to_str()
+        .ok_or_else(|| anyhow!("{} is not a valid UTF-8 path", path_ref.display()))?;
+
+    let mut expanded = String::with_capacity(value.len());
+    let mut last_end = 0;
+
+    for captures in REGEX_ENVIRONMENT_VARIABLE.captures_iter(value) {
+        let (Some(whole), Some(name)) = (captures.get(0), captures.get(1).or(captures.get(2)))
+        else {
+            continue;
+        };
+
+        let variable = env::var(name.as_str()).map_err(|e| {
+            anyhow!(e).context(format!(
+                "Unable to expand environment variable {} in {value}",
+                name.as_str()
+            ))
+        })?;
+
+        expanded.push_str(value.get(last_end..whole.start()).unwrap_or_default());
+        expanded.push_str(&variable);
+        last_end = whole.end();
+    }
+
+    expanded.push_str(value.get(last_end..).unwrap_or_default());
+
+    expand_home(Path::new(&expanded))
+}
+
+/// Replaces the home indicator, "~", at the start of a path with the user's home directory.
+fn expand_home(path: &Path) -> Result<PathBuf, Error> {
+    if !path.starts_with(HOME_INDICATOR) {
+        return Ok(path.to_path_buf());
+    }
+
+    #[cfg(unix)]
+    let home_dir = env::var("HOME")?;
+    #[cfg(windows)]
+    let home_dir = env::var("USERPROFILE")?;
+
+    let stripped_path = path
+        .strip_prefix(HOME_INDICATOR)
+        .map_err(|e| anyhow!(e).context("Path does not begin with home indicator"))?;
+
+    Ok(PathBuf::from(home_dir).join(stripped_path))
+}
+
 /// Safely attempts to write to a file, expanding paths relative to the home indicating character, "~".
 ///
+/// Unless `overwrite` is set, the file is created exclusively and the write fails if anything
+/// already exists at the path, so that concurrent or repeated writes can never destroy a note.
+///
 /// # Errors
 ///
 /// This function may return an error if environment variables (`XDG_CONFIG_HOME`, `HOME`, `USERPROFILE`) cannot
-/// be accessed, or if there is an issue expanding the relative path into an absolute path.
+/// be accessed, if there is an issue expanding the relative path into an absolute path, or if the
+/// file already exists and `overwrite` is not set.
 ///
 /// # Example
 /// ```
-/// let path = ???;
-/// let content = ???;
-/// safe_write();
+/// let path = "~/Documents/notes/20240101T120000--example.txt";
+/// let content = "Hello, world!";
+/// safe_write(path, content, false)?;
 /// ```
-pub fn safe_write<P: AsRef<Path>, T: AsRef<[u8]>>(path: P, template: T) -> Result<(), Error> {
-    const HOME_INDICATOR: &str = "~";
+pub fn safe_write<P: AsRef<Path>, T: AsRef<[u8]>>(
+    path: P,
+    template: T,
+    overwrite: bool,
+) -> Result<(), Error> {
+    let output_path = expand_home(path.as_ref())?;
+
+    fs::create_dir_all(output_path.parent().unwrap_or(&output_path))?;
 
-    let output_path = {
-        let path_ref = path.as_ref();
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(overwrite)
+        .truncate(overwrite)
+        .create_new(!overwrite)
+        .open(&output_path)
+        .map_err(|e| match e.kind() {
+            ErrorKind::AlreadyExists => anyhow!(e).context(format!(
+                "Refusing to overwrite existing file {}",
+                output_path.display()
+            )),
+            _ => anyhow!(e).context(format!("Failed to write to {}", output_path.display())),
+        })?;
+
+    file.write_all(template.as_ref())
+        .map_err(|e| anyhow!(e).context(format!("Failed to write to {}", output_path.display())))
+}
+
+/// Reads the content of a file as UTF-8 text, returning `None` if it is not text, such as an image
+/// or encrypted attachment.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let content = read_text("./20240101T120000--diagram.png")?;
+/// assert_eq!(content, None);
+/// ```
+pub fn read_text<P: AsRef<Path>>(path: P) -> Result<Option<String>, Error> {
+    let bytes = fs::read(path.as_ref())
+        .map_err(|e| anyhow!(e).context(format!("Failed to read {}", path.as_ref().display())))?;
 
-        if path_ref.starts_with(HOME_INDICATOR) {
-            #[cfg(unix)]
-            let home_dir = env::var("HOME")?;
-            #[cfg(windows)]
-            let home_dir = env::var("USERPROFILE")?;
+    Ok(String::from_utf8(bytes).ok())
+}
 
-            let stripped_path = path_ref
-                .strip_prefix(HOME_INDICATOR)
-                .map_err(|e| anyhow!(e).context("Path does not begin with home indicator"))?;
+/// Safely moves a file to a new path without changing its content, preserving its permissions and
+/// timestamps.
+///
+/// Unless `overwrite` is set, the move is refused if a different file already exists at the new
+/// path.
+///
+/// # Errors
+///
+/// This function will return an error if a different file exists at the new path and `overwrite`
+/// is not set, or if the file cannot be moved.
+///
+/// # Example
+///
+/// ```
+/// safe_rename("./note.txt", "./20240101T120000--note.txt", false)?;
+/// ```
+pub fn safe_rename<P: AsRef<Path>, Q: AsRef<Path>>(
+    from: P,
+    to: Q,
+    overwrite: bool,
+) -> Result<(), Error> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+
+    ensure_vacant(from, to, overwrite)?;
+
+    fs::rename(from, to).map_err(|e| {
+        anyhow!(e).context(format!(
+            "Failed to move {} to {}",
+            from.display(),
+            to.display()
+        ))
+    })
+}
+
+/// Safely moves a file to a new path while replacing its content.
+///
+/// The updated content is written to a temporary file beside the destination, which is then
+/// moved into place, so that a crash can never leave a partially written note. The original
+/// file is removed afterwards as a separate step, so a crash in between leaves both the original
+/// and the updated file in place. When the content is unchanged this is equivalent to
+/// `safe_rename`.
+///
+/// The original file is checked for modifications just before the updated file is moved into
+/// place, and if it no longer matches the `original` content the operation is abandoned rather
+/// than discarding those changes. This narrows the window for losing concurrent edits, but does
+/// not close it: a change made between the check and the move is still overwritten.
+///
+/// # Errors
+///
+/// This function will return an error if a different file exists at the new path and `overwrite`
+/// is not set, if the file was modified since its original content was read, or if any of the
+/// file system operations fail.
+///
+/// # Example
+///
+/// ```
+/// let original = fs::read("./note.txt")?;
+/// let updated = b"New content";
+/// safe_replace("./note.txt", "./20240101T120000--note.txt", &original, updated, false)?;
+/// ```
+pub fn safe_replace<P: AsRef<Path>, Q: AsRef<Path>>(
+    from: P,
+    to: Q,
+    original: &[u8],
+    updated: &[u8],
+    overwrite: bool,
+) -> Result<(), Error> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+
+    if original == updated {
+        return safe_rename(from, to, overwrite);
+    }
+
+    ensure_vacant(from, to, overwrite)?;
+
+    let temp_path = {
+        let name = to
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid output path {}", to.display()))?
+            .to_string_lossy();
+
+        to.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+    };
+
+    let result = (|| {
+        safe_write(&temp_path, updated, true)?;
+        fs::set_permissions(&temp_path, fs::metadata(from)?.permissions())?;
+
+        ensure_unmodified(from, original)?;
+
+        fs::rename(&temp_path, to)?;
+
+        Ok::<(), Error>(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result.map_err(|e| {
+        e.context(format!(
+            "Failed to move {} to {}",
+            from.display(),
+            to.display()
+        ))
+    })?;
+
+    if !is_same_file(from, to) {
+        fs::remove_file(from).map_err(|e| {
+            anyhow!(e).context(format!("Failed to remove original file {}", from.display()))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Checks that a file still has the content it had when it was read, so that any changes made
+/// to it since then are not discarded.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, or if its content no longer
+/// matches `original`.
+///
+/// # Example
+///
+/// ```
+/// let original = fs::read("./note.txt")?;
+/// ensure_unmodified("./note.txt", &original)?;
+/// ```
+pub fn ensure_unmodified<P: AsRef<Path>>(path: P, original: &[u8]) -> Result<(), Error> {
+    let path = path.as_ref();
+    let content = fs::read(path)
+        .map_err(|e| anyhow!(e).context(format!("Failed to read {}", path.display())))?;
+
+    if content != original {
+        return Err(anyhow!(
+            "{} was modified since it was read, leaving it untouched",
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that nothing other than the source file itself exists at a destination path, unless
+/// overwriting is allowed.
+fn ensure_vacant(from: &Path, to: &Path, overwrite: bool) -> Result<(), Error> {
+    if to.exists() && !is_same_file(from, to) && !overwrite {
+        return Err(anyhow!(
+            "{} already exists, use --force to overwrite it",
+            to.display()
+        ));
+    }
+
+    Ok(())
+}
 
-            PathBuf::from(home_dir).join(stripped_path)
-        } else {
-            path_ref.into()
+/// Returns whether two paths refer to the same file, such as on case-insensitive file systems.
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns whether a path lies within a directory or any of its subdirectories, resolving
+/// symbolic links and relative paths first. Paths which do not exist are never within it.
+pub fn is_within(path: &Path, directory: &Path) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(directory)) {
+        (Ok(path), Ok(directory)) => path.starts_with(directory),
+        _ => false,
+    }
+}
+
+/// Recursively collects the paths of every file within a directory, skipping hidden files and
+/// directories such as `.git`.
+///
+/// Symbolic links are followed, with each directory read only once so that links back into the
+/// tree cannot cause a cycle. Broken links and anything other than regular files and directories
+/// are skipped.
+///
+/// The returned paths are sorted by file name, which orders dn notes by their identifiers.
+///
+/// # Errors
+///
+/// This function will return an error if the directory or any of its subdirectories cannot be
+/// read.
+///
+/// # Example
+///
+/// ```
+/// let notes = read_notes("~/Documents/notes")?;
+/// ```
+pub fn read_notes<P: AsRef<Path>>(directory: P) -> Result<Vec<PathBuf>, Error> {
+    let mut pending = vec![directory.as_ref().to_path_buf()];
+    let mut visited = HashSet::new();
+    let mut notes = Vec::new();
+
+    while let Some(current) = pending.pop() {
+        let canonical = fs::canonicalize(&current).map_err(|e| {
+            anyhow!(e).context(format!("Failed to read directory {}", current.display()))
+        })?;
+
+        if !visited.insert(canonical) {
+            continue;
         }
+
+        let entries = fs::read_dir(&current).map_err(|e| {
+            anyhow!(e).context(format!("Failed to read directory {}", current.display()))
+        })?;
+
+        for entry in entries {
+            let entry = entry?;
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+
+            if is_hidden {
+                continue;
+            }
+
+            let path = entry.path();
+
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if metadata.is_file() {
+                notes.push(path);
+            }
+        }
+    }
+
+    notes.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+
+    Ok(notes)
+}
+
+/// Collects the identifiers of every note within a directory and its subdirectories, returning an
+/// empty set if the directory does not exist yet.
+///
+/// # Errors
+///
+/// This function will return an error if the directory exists but cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let identifiers = read_identifiers("~/Documents/notes")?;
+/// ```
+pub fn read_identifiers<P: AsRef<Path>>(directory: P) -> Result<HashSet<String>, Error> {
+    // NOTE: The parent of a bare file name is empty, which refers to the working directory.
+    let directory = match directory.as_ref() {
+        d if d.as_os_str().is_empty() => Path::new("."),
+        d => d,
     };
 
-    fs::create_dir_all(output_path.parent().unwrap_or(&output_path))?;
-    fs::write(&output_path, template)
-        .map_err(|e| anyhow!(e).context(format!("Failed to write to {}", output_path.display())))
+    if !directory.is_dir() {
+        return Ok(HashSet::new());
+    }
+
+    let identifiers = read_notes(directory)?
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str().and_then(parse_identifier))
+        .collect();
+
+    Ok(identifiers)
+}
+
+/// Collects the distinct keywords of every note within a directory and its subdirectories, sorted
+/// alphabetically, returning an empty list if the directory does not exist yet.
+///
+/// # Errors
+///
+/// This function will return an error if the directory exists but cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let keywords = read_keywords("~/Documents/notes")?;
+/// ```
+pub fn read_keywords<P: AsRef<Path>>(directory: P) -> Result<Vec<String>, Error> {
+    let directory = match directory.as_ref() {
+        d if d.as_os_str().is_empty() => Path::new("."),
+        d => d,
+    };
+
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let keywords = read_notes(directory)?
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str().map(parse_keywords))
+        .flatten()
+        .collect::<BTreeSet<_>>();
+
+    Ok(keywords.into_iter().collect())
 }
 
 ///////////
@@ -128,4 +532,182 @@ pub fn safe_write<P: AsRef<Path>, T: AsRef<[u8]>>(path: P, template: T) -> Resul
 ///////////
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::testing::TempDirectory;
+
+    #[cfg(unix)]
+    #[test]
+    fn expand_path_expands_home_and_variables() {
+        // Arrange
+        let home = env::var("HOME").unwrap_or_default();
+        let input = "~/templates/${HOME}/$HOME.txt";
+        let expected = PathBuf::from(&home)
+            .join("templates")
+            .join(format!("{}/{home}.txt", home.trim_start_matches('/')));
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "HOME is always set on unix")]
+        let result = expand_path(input).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn expand_path_rejects_unset_variables() {
+        // Arrange
+        let input = "$DN_TEST_UNSET_VARIABLE/template.txt";
+
+        // Act
+        let result = expand_path(input);
+
+        // Assert
+        assert!(result.is_err(), "Expected an unset variable error");
+    }
+
+    #[test]
+    fn safe_write_refuses_to_overwrite() {
+        // Arrange
+        let directory = TempDirectory::new("refuse");
+        let path = directory.path().join("20240101T120000--note.txt");
+
+        // Act
+        let first = safe_write(&path, "first", false);
+        let second = safe_write(&path, "second", false);
+
+        // Assert
+        assert!(first.is_ok(), "Expected the first write to succeed");
+        assert!(second.is_err(), "Expected the second write to be refused");
+        assert_eq!("first", fs::read_to_string(&path).unwrap_or_default());
+    }
+
+    #[test]
+    fn safe_write_overwrites_when_allowed() {
+        // Arrange
+        let directory = TempDirectory::new("overwrite");
+        let path = directory.path().join("20240101T120000--note.txt");
+
+        // Act
+        let first = safe_write(&path, "first content", false);
+        let second = safe_write(&path, "second", true);
+
+        // Assert
+        assert!(first.is_ok(), "Expected the first write to succeed");
+        assert!(second.is_ok(), "Expected the overwrite to succeed");
+        assert_eq!("second", fs::read_to_string(&path).unwrap_or_default());
+    }
+
+    #[test]
+    fn read_text_ignores_binary_content() {
+        // Arrange
+        let directory = TempDirectory::new("binary");
+        let text = directory.write("note.txt", "text");
+        let binary = directory.write("image.png", [0x89, 0x50, 0x4e, 0x47, 0xff, 0xfe]);
+
+        // Act
+        let result_text = read_text(&text).ok().flatten();
+        let result_binary = read_text(&binary).ok().flatten();
+
+        // Assert
+        assert_eq!(Some("text".to_owned()), result_text);
+        assert_eq!(None, result_binary);
+    }
+
+    #[test]
+    fn safe_replace_moves_and_rewrites() {
+        // Arrange
+        let directory = TempDirectory::new("replace");
+        let from = directory.write("note.txt", "old");
+        let to = directory.path().join("20240101T120000--note.txt");
+
+        // Act
+        let result = safe_replace(&from, &to, b"old", b"new", false);
+
+        // Assert
+        assert!(result.is_ok(), "Expected the replace to succeed");
+        assert!(!from.exists(), "Expected the original file to be removed");
+        assert_eq!("new", fs::read_to_string(&to).unwrap_or_default());
+    }
+
+    #[test]
+    fn safe_replace_abandons_modified_files() {
+        // Arrange
+        let directory = TempDirectory::new("modified");
+        let from = directory.write("note.txt", "edited elsewhere");
+        let to = directory.path().join("20240101T120000--note.txt");
+
+        // Act
+        let result = safe_replace(&from, &to, b"old", b"new", false);
+
+        // Assert
+        assert!(result.is_err(), "Expected the replace to be abandoned");
+        assert!(!to.exists(), "Expected no file at the new path");
+        assert_eq!(
+            "edited elsewhere",
+            fs::read_to_string(&from).unwrap_or_default()
+        );
+        assert_eq!(1, fs::read_dir(directory.path()).map_or(0, Iterator::count));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_notes_follows_symlinks_without_cycles() {
+        use std::os::unix::fs::symlink;
+
+        // Arrange
+        let directory = TempDirectory::new("symlinks");
+        let note = directory.write("notes/20240101T120000--a.txt", "");
+        directory.write("elsewhere/20240102T120000--b.txt", "");
+        let notes = directory.path().join("notes");
+        symlink(directory.path().join("elsewhere"), notes.join("linked"))
+            .expect("Unable to link test directory");
+        symlink(&notes, notes.join("cycle")).expect("Unable to link test directory");
+        symlink(notes.join("missing.txt"), notes.join("broken.txt"))
+            .expect("Unable to link test file");
+        let expected = vec![note, notes.join("linked").join("20240102T120000--b.txt")];
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The directory was just written")]
+        let result = read_notes(&notes).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn is_within_checks_directory_tree() {
+        // Arrange
+        let directory = TempDirectory::new("within");
+        let inside = directory.write("notes/sub/20240101T120000--a.txt", "");
+        let outside = directory.write("elsewhere/20240102T120000--b.txt", "");
+        let notes = directory.path().join("notes");
+
+        // Act
+        let results = [
+            is_within(&inside, &notes),
+            is_within(&outside, &notes),
+            is_within(&inside, &directory.path().join("missing")),
+        ];
+
+        // Assert
+        assert_eq!([true, false, false], results);
+    }
+
+    #[test]
+    fn safe_rename_refuses_to_overwrite() {
+        // Arrange
+        let directory = TempDirectory::new("rename");
+        let from = directory.write("note.txt", "from");
+        let to = directory.write("20240101T120000--note.txt", "to");
+
+        // Act
+        let refused = safe_rename(&from, &to, false);
+        let forced = safe_rename(&from, &to, true);
+
+        // Assert
+        assert!(refused.is_err(), "Expected the rename to be refused");
+        assert!(forced.is_ok(), "Expected the forced rename to succeed");
+        assert_eq!("from", fs::read_to_string(&to).unwrap_or_default());
+    }
+}
@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Utilities for opening notes in the user's text editor.
+
+use std::{
+    env,
+    fs::OpenOptions,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{anyhow, Error};
+
+use crate::config::Config;
+
+/// Opens a file in the user's editor and waits for the editor to exit.
+///
+/// The editor is the `editor` configuration value, falling back to the `VISUAL` and then `EDITOR`
+/// environment variables, and is split on whitespace into a program and its arguments. When
+/// `line` is given and `editor_line_argument` is enabled, a `+line` argument is passed before the
+/// file so that the cursor starts on that line.
+///
+/// The editor is attached to the terminal even when dn's own input or output is redirected, so
+/// that terminal editors keep working at the end of a pipeline.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if no editor is configured, if the editor cannot be started, or if
+/// it exits unsuccessfully.
+///
+/// # Example
+///
+/// ```
+/// open_in_editor(&config, Path::new("20240101T120000--note.txt"), Some(6))?;
+/// ```
+pub fn open_in_editor(config: &Config, path: &Path, line: Option<usize>) -> Result<(), Error> {
+    let editor = config
+        .editor
+        .clone()
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow!("No editor found: Set `editor` in the configuration file or the EDITOR environment variable")
+        })?;
+
+    let mut command = editor_command(&editor, path, line.filter(|_| config.editor_line_argument))?;
+    attach_terminal(&mut command);
+
+    let status = command
+        .status()
+        .map_err(|e| anyhow!(e).context(format!("Error starting editor \"{editor}\"")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("Editor \"{editor}\" failed with {status}"))
+    }
+}
+
+/// Builds the command which opens a file in an editor, given as a program followed by any
+/// arguments separated by whitespace.
+fn editor_command(editor: &str, path: &Path, line: Option<usize>) -> Result<Command, Error> {
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow!("No editor found: The editor command is empty"))?;
+
+    let mut command = Command::new(program);
+    command.args(parts);
+
+    if let Some(l) = line {
+        command.arg(format!("+{l}"));
+    }
+
+    command.arg(path);
+
+    Ok(command)
+}
+
+/// Connects the standard streams of a command to the controlling terminal where there is one,
+/// leaving them inherited otherwise.
+fn attach_terminal(command: &mut Command) {
+    #[cfg(unix)]
+    let terminal = "/dev/tty";
+    #[cfg(windows)]
+    let terminal = "CON";
+
+    let open = || OpenOptions::new().read(true).write(true).open(terminal);
+
+    if let (Ok(stdin), Ok(stdout)) = (open(), open()) {
+        command
+            .stdin(Stdio::from(stdin))
+            .stdout(Stdio::from(stdout));
+    }
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_command_splits_arguments_and_adds_line() {
+        // Arrange
+        let path = Path::new("my notes/20240101T120000--note.txt");
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The editor command is not empty")]
+        let command = editor_command("code --wait", path, Some(6)).unwrap();
+
+        // Assert
+        assert_eq!(command.get_program(), "code");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["--wait", "+6", "my notes/20240101T120000--note.txt"]
+        );
+    }
+
+    #[test]
+    fn editor_command_rejects_empty_editor() {
+        // Arrange
+        let path = Path::new("note.txt");
+
+        // Act
+        let result = editor_command("  ", path, None);
+
+        // Assert
+        assert!(
+            result.is_err(),
+            "Expected an empty editor command to be rejected"
+        );
+    }
+}
@@ -17,7 +17,8 @@
 
 /// Regex to match the `Identifier` segment of a file name.
 static REGEX_SEGMENT_IDENTIFIER: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"(\b[0-9]{8}T[0-9]{6}\b)").expect("Invalid identifier segment regex pattern")
+    Regex::new("(?:^|[^0-9])([0-9]{8}T[0-9]{6})(?:[^0-9]|$)")
+        .expect("Invalid identifier segment regex pattern")
 });
 
 /// Regex to match the `Signature` segment of a file name.
@@ -159,17 +160,65 @@ fn to_filename(&self, config: &Config) -> Filename {
         }
     }
 }
-/// Attempts to parse a segment from a filename using the provided regex, returning it as an `Option<String>`.
+/// Returns whether a file name conforms to the dn naming scheme, i.e. whether it contains an
+/// identifier segment.
 ///
 /// # Example
 ///
 /// ```
-/// let regex = LazyLock::new(|| Regex::new(r"\d+").unwrap());
+/// assert!(is_conforming("20240101T120000--title.txt"));
+/// assert!(!is_conforming("title.txt"));
+/// ```
+pub fn is_conforming(filename: &str) -> bool {
+    REGEX_SEGMENT_IDENTIFIER.is_match(filename)
+}
+
+/// Attempts to parse the identifier from a file name, returning `None` if it does not have one.
+///
+/// # Example
+///
+/// ```
+/// let identifier = parse_identifier("--title@@20240101T120000.txt");
+/// assert_eq!(identifier, Some("20240101T120000".to_owned()));
+/// ```
+pub fn parse_identifier(filename: &str) -> Option<String> {
+    parse_segment(filename, &REGEX_SEGMENT_IDENTIFIER)
+}
+
+/// Parses the keywords from a file name, returning an empty list if it has none.
+///
+/// # Example
+///
+/// ```
+/// let keywords = parse_keywords("20240101T120000--title__work_meeting.txt");
+/// assert_eq!(keywords, vec!["work".to_owned(), "meeting".to_owned()]);
+/// ```
+pub fn parse_keywords(filename: &str) -> Vec<String> {
+    parse_segment(filename, &REGEX_SEGMENT_KEYWORDS)
+        .map(|k| {
+            k.split('_')
+                .filter(|k| !k.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Attempts to parse a segment from a filename using the first capture group of the provided regex,
+/// returning it as an `Option<String>`.
+///
+/// # Example
+///
+/// ```
+/// let regex = LazyLock::new(|| Regex::new(r"(\d+)").unwrap());
 /// let result = parse_segment("file123.txt", &regex);
 /// assert_eq!(result, Some("123".to_string()));
 /// ```
 fn parse_segment(filename: &str, regex: &LazyLock<Regex>) -> Option<String> {
-    regex.find(filename).map(|m| m.as_str().to_owned())
+    regex
+        .captures(filename)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_owned())
 }
 
 /// Applies a prefix corresponding to the `FilenameSegment` variant to an input string.
@@ -245,6 +294,48 @@ fn string_to_filename_non_dn_format() {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn is_conforming_requires_identifier() {
+        // Arrange
+        let test_cases = [
+            ("20240101T120000--title__keywords.txt", true),
+            ("--title==sig@@20240101T120000.md", true),
+            ("20240101T120000__keywords.md", true),
+            ("120240101T120000--title.md", false),
+            ("2024-01-01-title.txt", false),
+            ("title.txt", false),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = is_conforming(input);
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn parse_keywords_splits_keyword_segment() {
+        // Arrange
+        let test_cases = [
+            (
+                "20240101T120000--title__work_meeting.txt",
+                vec!["work", "meeting"],
+            ),
+            ("20240101T120000__solo==sig.md", vec!["solo"]),
+            ("20240101T120000--title.md", vec![]),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = parse_keywords(input);
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
     #[test]
     fn metadata_to_filename_full() {
         // Arrange
@@ -253,6 +344,7 @@ fn metadata_to_filename_full() {
             identifier: "20240101T120000".to_owned(),
             signature: Some("test-sig".to_owned()),
             title: Some("test-title".to_owned()),
+            title_raw: Some("Test Title".to_owned()),
             keywords: Some(vec!["key1".to_owned(), "key2".to_owned()]),
             extension: "txt".to_owned(),
         };
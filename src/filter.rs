@@ -0,0 +1,281 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Filtering of existing notes by the metadata encoded in their file names.
+
+use anyhow::{anyhow, Error};
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{
+    config::Config,
+    filename::Filename,
+    metadata::{FileMetadata, DN_IDENTIFIER_FORMAT},
+};
+
+/// A `mut self` builder that allows progressively updating an input state for a new `NoteFilter`.
+#[derive(Debug, Default)]
+pub struct NoteFilterBuilder {
+    signature: Option<String>,
+    title: Option<String>,
+    keywords: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// The criteria a note's file name must satisfy to be included in a listing.
+///
+/// Each criterion is optional, and a note matches when it satisfies all of those present.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NoteFilter {
+    /// The exact signature the note must have.
+    pub signature: Option<String>,
+    /// A fragment which must appear within the note's title.
+    pub title: Option<String>,
+    /// Keywords which must all be present on the note.
+    pub keywords: Option<Vec<String>>,
+    /// The earliest identifier, inclusive, that the note may have.
+    pub from: Option<String>,
+    /// The latest identifier, inclusive, that the note may have.
+    pub to: Option<String>,
+}
+
+impl NoteFilter {
+    /// Creates a new builder initialised with default values.
+    pub fn builder() -> NoteFilterBuilder {
+        NoteFilterBuilder::default()
+    }
+
+    /// Returns whether a parsed file name satisfies every criterion of the filter.
+    pub fn matches(&self, filename: &Filename) -> bool {
+        let signature = filename
+            .signature
+            .as_deref()
+            .map(|s| s.trim_start_matches('='));
+        let title = filename.title.as_deref().map(|t| t.trim_start_matches('-'));
+        let keywords = filename
+            .keywords
+            .as_deref()
+            .map(|k| k.split('_').filter(|k| !k.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let identifier = filename.identifier.trim_start_matches('@');
+
+        let signature_matches = self
+            .signature
+            .as_deref()
+            .map_or(true, |s| signature == Some(s));
+        let title_matches = self
+            .title
+            .as_deref()
+            .map_or(true, |t| title.is_some_and(|title| title.contains(t)));
+        let keywords_match = self
+            .keywords
+            .as_ref()
+            .map_or(true, |ks| ks.iter().all(|k| keywords.contains(&k.as_str())));
+        let from_matches = self.from.as_deref().map_or(true, |f| identifier >= f);
+        let to_matches = self.to.as_deref().map_or(true, |t| identifier <= t);
+
+        signature_matches && title_matches && keywords_match && from_matches && to_matches
+    }
+}
+
+impl NoteFilterBuilder {
+    /// Optionally adds a signature to the builder.
+    pub fn with_signature(mut self, value: Option<&str>) -> Self {
+        self.signature.clone_from(&value.map(String::from));
+        self
+    }
+
+    /// Optionally adds a title fragment to the builder.
+    pub fn with_title(mut self, value: Option<&str>) -> Self {
+        self.title.clone_from(&value.map(String::from));
+        self
+    }
+
+    /// Optionally adds required keywords to the builder.
+    pub fn with_keywords(mut self, value: Option<&str>) -> Self {
+        self.keywords.clone_from(&value.map(String::from));
+        self
+    }
+
+    /// Optionally adds the start of the identifier date range to the builder.
+    pub fn with_from(mut self, value: Option<&str>) -> Self {
+        self.from.clone_from(&value.map(String::from));
+        self
+    }
+
+    /// Optionally adds the end of the identifier date range to the builder.
+    pub fn with_to(mut self, value: Option<&str>) -> Self {
+        self.to.clone_from(&value.map(String::from));
+        self
+    }
+
+    /// Builds the final `NoteFilter`, sanitising the signature, title, and keywords in the same
+    /// way as they would be when creating a note so that they can be compared with file names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if either end of the date range is neither a `YYYY-MM-DD` date
+    /// nor a dn identifier.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let filter = NoteFilter::builder()
+    ///     .with_keywords(Some("Journal"))
+    ///     .with_from(Some("2024-01-01"))
+    ///     .build(&config)?;
+    /// assert_eq!(filter.from, Some("20240101T000000".to_owned()));
+    /// ```
+    pub fn build(&self, config: &Config) -> Result<NoteFilter, Error> {
+        let metadata = FileMetadata::builder()
+            .with_signature(self.signature.as_deref())
+            .with_title(self.title.as_deref())
+            .with_keywords(self.keywords.as_deref())
+            .build(config);
+
+        let from = self
+            .from
+            .as_deref()
+            .map(|f| parse_date_bound(f, "000000"))
+            .transpose()?;
+
+        let to = self
+            .to
+            .as_deref()
+            .map(|t| parse_date_bound(t, "235959"))
+            .transpose()?;
+
+        Ok(NoteFilter {
+            signature: metadata.signature,
+            title: metadata.title,
+            keywords: metadata.keywords,
+            from,
+            to,
+        })
+    }
+}
+
+/// Parses a date range bound into a dn identifier, using the provided time of day when only a
+/// date is given.
+///
+/// # Example
+///
+/// ```
+/// let bound = parse_date_bound("2024-01-31", "235959")?;
+/// assert_eq!(bound, "20240131T235959");
+/// ```
+fn parse_date_bound(value: &str, time: &str) -> Result<String, Error> {
+    if NaiveDateTime::parse_from_str(value, DN_IDENTIFIER_FORMAT).is_ok() {
+        return Ok(value.to_owned());
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| format!("{}T{time}", d.format("%Y%m%d")))
+        .map_err(|e| {
+            anyhow!(e).context(format!(
+                "Invalid date '{value}': Expected YYYY-MM-DD or an identifier"
+            ))
+        })
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filename::ToFilename;
+
+    #[test]
+    fn builder_sanitises_and_parses_bounds() {
+        // Arrange
+        let config = Config::default();
+        let input = NoteFilter::builder()
+            .with_signature(Some("1A"))
+            .with_title(Some("Weekly Review"))
+            .with_keywords(Some("Work_meeting"))
+            .with_from(Some("2024-01-01"))
+            .with_to(Some("20240131T120000"));
+        let expected = NoteFilter {
+            signature: Some("1a".to_owned()),
+            title: Some("weekly-review".to_owned()),
+            keywords: Some(vec!["meeting".to_owned(), "work".to_owned()]),
+            from: Some("20240101T000000".to_owned()),
+            to: Some("20240131T120000".to_owned()),
+        };
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "Both bounds are valid")]
+        let result = input.build(&config).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_bounds() {
+        // Arrange
+        let config = Config::default();
+        let input = NoteFilter::builder().with_to(Some("last tuesday"));
+
+        // Act
+        let result = input.build(&config);
+
+        // Assert
+        assert!(result.is_err(), "Expected an invalid date error");
+    }
+
+    #[test]
+    fn filter_matches_all_criteria() {
+        // Arrange
+        let config = Config::default();
+        let filename = "20240115T093000==1a--weekly-review-notes__meeting_work.md"
+            .to_owned()
+            .to_filename(&config);
+        let test_cases = [
+            (NoteFilter::default(), true),
+            (
+                NoteFilter {
+                    signature: Some("1a".to_owned()),
+                    title: Some("review".to_owned()),
+                    keywords: Some(vec!["work".to_owned()]),
+                    from: Some("20240101T000000".to_owned()),
+                    to: Some("20240131T235959".to_owned()),
+                },
+                true,
+            ),
+            (
+                NoteFilter {
+                    keywords: Some(vec!["work".to_owned(), "personal".to_owned()]),
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                NoteFilter {
+                    signature: Some("1".to_owned()),
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                NoteFilter {
+                    to: Some("20240115T092959".to_owned()),
+                    ..Default::default()
+                },
+                false,
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = input.matches(&filename);
+
+            // Assert
+            assert_eq!(expected, result, "Filter: {input:?}");
+        }
+    }
+}
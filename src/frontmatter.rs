@@ -0,0 +1,1172 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Serialisation of note metadata into Denote-compatible front matter blocks.
+
+use anyhow::{anyhow, Error};
+use chrono::NaiveDateTime;
+
+use crate::{
+    config::{Config, MarkdownFrontmatterFormat},
+    metadata::{identifier_date, FileMetadata, FileMetadataBuilder, DN_IDENTIFIER_FORMAT},
+};
+
+/// The line which closes a plain text front matter block.
+const TEXT_FRONTMATTER_SEPARATOR: &str = "---------------------------";
+
+/// The fields which are rewritten when synchronising an existing front matter block.
+const SYNCED_FIELDS: [FrontmatterField; 4] = [
+    FrontmatterField::Title,
+    FrontmatterField::Keywords,
+    FrontmatterField::Identifier,
+    FrontmatterField::Signature,
+];
+
+/// The supported front matter syntaxes, following the file types understood by Denote.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrontmatterFormat {
+    Text,
+    Yaml,
+    Toml,
+    Org,
+}
+
+/// The individual fields which dn writes to a front matter block, in the order they appear.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrontmatterField {
+    Title,
+    Date,
+    Keywords,
+    Identifier,
+    Signature,
+}
+
+/// The raw values of the fields managed by dn, as read from an existing front matter block.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    pub identifier: Option<String>,
+    pub signature: Option<String>,
+    pub title: Option<String>,
+    pub keywords: Option<String>,
+}
+
+/// The location of a front matter block within the lines of a note.
+#[derive(Debug, PartialEq, Eq)]
+struct FrontmatterBlock {
+    format: FrontmatterFormat,
+    /// The index of the first field line.
+    start: usize,
+    /// The index one past the last field line, which is the closing delimiter if there is one.
+    end: usize,
+}
+
+/// A trait for converting a value to a front matter block.
+///
+/// # Example
+///
+/// ```
+/// let config = Config::default();
+/// let frontmatter = metadata.to_frontmatter(&config);
+/// ```
+pub trait ToFrontmatter {
+    /// Converts the value to a front matter block, returning `None` if front matter is disabled
+    /// or there is no front matter format associated with the value's file type.
+    fn to_frontmatter(&self, config: &Config) -> Option<String>;
+}
+
+impl FrontmatterFormat {
+    /// Determines the front matter format for a file extension, returning `None` if the file
+    /// type does not support front matter.
+    ///
+    /// Only the final part of a chained extension such as `tar.gz` is considered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let format = FrontmatterFormat::from_extension("org", &config);
+    /// assert_eq!(format, Some(FrontmatterFormat::Org));
+    /// ```
+    pub fn from_extension(extension: &str, config: &Config) -> Option<Self> {
+        match extension.rsplit('.').next() {
+            Some("txt") => Some(Self::Text),
+            Some("org") => Some(Self::Org),
+            Some("md") => match config.markdown_frontmatter_format {
+                MarkdownFrontmatterFormat::Yaml => Some(Self::Yaml),
+                MarkdownFrontmatterFormat::Toml => Some(Self::Toml),
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the front matter formats which a note with a file extension may contain, so that
+    /// the content of other file types is never mistaken for front matter.
+    ///
+    /// Both YAML and TOML are recognised in Markdown notes, whichever is configured for new notes.
+    fn allowed_for(extension: &str) -> &'static [Self] {
+        match extension.trim_start_matches('.').rsplit('.').next() {
+            Some("txt") => &[Self::Text],
+            Some("org") => &[Self::Org],
+            Some("md") => &[Self::Yaml, Self::Toml],
+            _ => &[],
+        }
+    }
+
+    /// Returns the lines which open and close a front matter block in this format.
+    fn delimiters(self) -> (Option<&'static str>, Option<&'static str>) {
+        match self {
+            Self::Text => (None, Some(TEXT_FRONTMATTER_SEPARATOR)),
+            Self::Yaml => (Some("---"), Some("---")),
+            Self::Toml => (Some("+++"), Some("+++")),
+            Self::Org => (None, None),
+        }
+    }
+}
+
+impl FrontmatterBlock {
+    /// Returns the index of the first line after the block, past any closing delimiter.
+    fn body_start(&self) -> usize {
+        match self.format {
+            FrontmatterFormat::Org => self.end,
+            _ => self.end + 1,
+        }
+    }
+}
+
+impl Frontmatter {
+    /// Applies each value present in the front matter to a metadata builder, leaving the
+    /// builder's existing values in place for any fields which are absent.
+    pub fn apply(&self, mut builder: FileMetadataBuilder) -> FileMetadataBuilder {
+        if self.identifier.is_some() {
+            builder = builder.with_identifier(self.identifier.as_deref());
+        }
+
+        if self.signature.is_some() {
+            builder = builder.with_signature(self.signature.as_deref());
+        }
+
+        if self.title.is_some() {
+            builder = builder.with_title(self.title.as_deref());
+        }
+
+        if self.keywords.is_some() {
+            builder = builder.with_keywords(self.keywords.as_deref());
+        }
+
+        builder
+    }
+}
+
+impl ToFrontmatter for FileMetadata {
+    fn to_frontmatter(&self, config: &Config) -> Option<String> {
+        if !config.frontmatter_enabled {
+            return None;
+        }
+
+        let format = FrontmatterFormat::from_extension(&self.extension, config)?;
+        let (open, close) = format.delimiters();
+
+        let fields = [
+            FrontmatterField::Title,
+            FrontmatterField::Date,
+            FrontmatterField::Keywords,
+            FrontmatterField::Identifier,
+            FrontmatterField::Signature,
+        ]
+        .into_iter()
+        .filter_map(|f| format_field(self, format, f));
+
+        let lines = open
+            .into_iter()
+            .map(String::from)
+            .chain(fields)
+            .chain(close.map(String::from))
+            .collect::<Vec<_>>();
+
+        Some(format!("{}\n\n", lines.join("\n")))
+    }
+}
+
+/// Formats a single front matter line for the given field, returning `None` if the field should
+/// be omitted.
+///
+/// The title, keywords, and identifier are always present so that they can be filled in later,
+/// while the date and signature are omitted when there is no value for them.
+///
+/// # Example
+///
+/// ```
+/// let line = format_field(&metadata, FrontmatterFormat::Org, FrontmatterField::Title);
+/// assert_eq!(line, Some("#+title:      My Note".to_owned()));
+/// ```
+pub fn format_field(
+    metadata: &FileMetadata,
+    format: FrontmatterFormat,
+    field: FrontmatterField,
+) -> Option<String> {
+    let value = match field {
+        FrontmatterField::Title => {
+            let title = metadata.title_raw.clone().unwrap_or_default();
+
+            match format {
+                FrontmatterFormat::Yaml | FrontmatterFormat::Toml => quote(&title),
+                FrontmatterFormat::Text | FrontmatterFormat::Org => title,
+            }
+        }
+        FrontmatterField::Date => {
+            let date = identifier_date(&metadata.identifier)?;
+
+            match format {
+                FrontmatterFormat::Yaml | FrontmatterFormat::Toml => {
+                    date.format("%Y-%m-%dT%H:%M:%S%:z").to_string()
+                }
+                FrontmatterFormat::Org => date.format("[%Y-%m-%d %a %H:%M]").to_string(),
+                FrontmatterFormat::Text => date.format("%Y-%m-%d").to_string(),
+            }
+        }
+        FrontmatterField::Keywords => {
+            let keywords = metadata.keywords.clone().unwrap_or_default();
+
+            match format {
+                FrontmatterFormat::Yaml | FrontmatterFormat::Toml => format!(
+                    "[{}]",
+                    keywords
+                        .iter()
+                        .map(|k| quote(k))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                FrontmatterFormat::Org if keywords.is_empty() => String::new(),
+                FrontmatterFormat::Org => format!(":{}:", keywords.join(":")),
+                FrontmatterFormat::Text => keywords.join("  "),
+            }
+        }
+        FrontmatterField::Identifier => match format {
+            FrontmatterFormat::Yaml | FrontmatterFormat::Toml => quote(&metadata.identifier),
+            FrontmatterFormat::Text | FrontmatterFormat::Org => metadata.identifier.clone(),
+        },
+        FrontmatterField::Signature => {
+            let signature = metadata.signature.as_ref()?;
+
+            match format {
+                FrontmatterFormat::Yaml | FrontmatterFormat::Toml => quote(signature),
+                FrontmatterFormat::Text | FrontmatterFormat::Org => signature.clone(),
+            }
+        }
+    };
+
+    let key = field_key(format, field);
+    let line = match format {
+        FrontmatterFormat::Yaml | FrontmatterFormat::Text => {
+            format!("{:<12}{value}", format!("{key}:"))
+        }
+        FrontmatterFormat::Toml => format!("{key:<10} = {value}"),
+        FrontmatterFormat::Org => format!("{:<14}{value}", format!("#+{key}:")),
+    };
+
+    Some(line.trim_end().to_owned())
+}
+
+/// Rewrites the title, keywords, identifier, and signature fields of the front matter block at the
+/// start of a note to match the provided metadata, returning `None` if there is no front matter.
+/// Only the front matter formats allowed by the note's current `extension` are recognised.
+///
+/// Fields which are not managed by dn, as well as the rest of the note, are left unchanged. The
+/// existing title is kept as written if it sanitises to the same value as the metadata title.
+/// Managed fields which are missing from the block are appended to it.
+///
+/// If the metadata's extension calls for a different format, the block is regenerated in that
+/// format instead.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the block must change format but contains fields which dn does
+/// not manage, as they cannot be carried over.
+///
+/// # Example
+///
+/// ```
+/// let content = "#+title: Old\n#+author: Me\n\nBody";
+/// let updated = update_frontmatter(content, "org", &metadata, &config)?;
+/// assert_eq!(updated, Some("#+title:      New\n#+author: Me\n...".to_owned()));
+/// ```
+pub fn update_frontmatter(
+    content: &str,
+    extension: &str,
+    metadata: &FileMetadata,
+    config: &Config,
+) -> Result<Option<String>, Error> {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let Some(block) = find_frontmatter(&lines, extension) else {
+        return Ok(None);
+    };
+    let format = block.format;
+    let newline = if lines.first().is_some_and(|l| l.ends_with("\r\n")) {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let is_converted = FrontmatterFormat::from_extension(&metadata.extension, config)
+        .is_some_and(|_| !FrontmatterFormat::allowed_for(&metadata.extension).contains(&format));
+
+    if is_converted {
+        let existing_title = lines
+            .iter()
+            .take(block.end)
+            .skip(block.start)
+            .find(|l| line_key(format, l).and_then(field_from_key) == Some(FrontmatterField::Title))
+            .filter(|l| title_unchanged(format, l, metadata, config))
+            .and_then(|l| line_value(format, l.trim_end()));
+        let metadata = FileMetadata {
+            title_raw: existing_title.or_else(|| metadata.title_raw.clone()),
+            ..metadata.clone()
+        };
+        let frontmatter = metadata.to_frontmatter(config).unwrap_or_default();
+
+        return convert_frontmatter(&lines, &block, &frontmatter, newline).map(Some);
+    }
+
+    let mut missing = SYNCED_FIELDS.to_vec();
+    let mut output = Vec::with_capacity(lines.len() + SYNCED_FIELDS.len());
+    let mut replacing = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if index == block.end {
+            push_fields(&mut output, metadata, format, &missing, newline);
+        }
+
+        if !(block.start..block.end).contains(&index) {
+            output.push((*line).to_owned());
+            continue;
+        }
+
+        let field = line_key(format, line)
+            .and_then(field_from_key)
+            .filter(|f| SYNCED_FIELDS.contains(f));
+
+        match field {
+            Some(FrontmatterField::Title) if title_unchanged(format, line, metadata, config) => {
+                missing.retain(|&m| m != FrontmatterField::Title);
+                replacing = false;
+                output.push((*line).to_owned());
+            }
+            Some(f) => {
+                missing.retain(|&m| m != f);
+                replacing = true;
+                output.extend(format_field(metadata, format, f).map(|l| format!("{l}{newline}")));
+            }
+            None if replacing && is_continuation(format, line) => {}
+            None => {
+                replacing = false;
+                output.push((*line).to_owned());
+            }
+        }
+    }
+
+    if block.end == lines.len() {
+        push_fields(&mut output, metadata, format, &missing, newline);
+    }
+
+    Ok(Some(output.concat()))
+}
+
+/// Attempts to read the fields managed by dn from the front matter block at the start of a note,
+/// returning `None` if there is no front matter in a format allowed by the note's `extension`.
+///
+/// Empty values are treated as absent, as are identifiers which are not in the dn format.
+///
+/// # Example
+///
+/// ```
+/// let content = "#+title: My Note\n#+filetags: :a:b:\n\nBody";
+/// let frontmatter = parse_frontmatter(content, "org");
+/// assert_eq!(frontmatter.unwrap().keywords, Some("a b".to_owned()));
+/// ```
+pub fn parse_frontmatter(content: &str, extension: &str) -> Option<Frontmatter> {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let block = find_frontmatter(&lines, extension)?;
+    let format = block.format;
+
+    let mut frontmatter = Frontmatter::default();
+    let mut keywords = Vec::new();
+    let mut in_keywords = false;
+
+    for line in lines.iter().take(block.end).skip(block.start) {
+        let line = line.trim_end();
+
+        match line_key(format, line).and_then(field_from_key) {
+            Some(FrontmatterField::Keywords) => {
+                in_keywords = true;
+                keywords.extend(split_keywords(&raw_value(format, line).unwrap_or_default()));
+            }
+            Some(field) => {
+                in_keywords = false;
+                let value = line_value(format, line).filter(|v| !v.is_empty());
+
+                match field {
+                    FrontmatterField::Title => frontmatter.title = value,
+                    FrontmatterField::Identifier => {
+                        frontmatter.identifier = value.filter(|v| {
+                            NaiveDateTime::parse_from_str(v, DN_IDENTIFIER_FORMAT).is_ok()
+                        });
+                    }
+                    FrontmatterField::Signature => frontmatter.signature = value,
+                    FrontmatterField::Date | FrontmatterField::Keywords => {}
+                }
+            }
+            None if in_keywords && is_continuation(format, line) => {
+                keywords.extend(split_keywords(line));
+            }
+            None => in_keywords = false,
+        }
+    }
+
+    frontmatter.keywords = (!keywords.is_empty()).then(|| keywords.join(" "));
+
+    Some(frontmatter)
+}
+
+/// Returns the one-based number of the line on which the body of a note begins, after its front
+/// matter block and any blank lines following it, or `1` if the note has no front matter in a
+/// format allowed by its `extension`. Notes without a body give their last line.
+///
+/// # Example
+///
+/// ```
+/// let content = "#+title: My Note\n\nBody";
+/// assert_eq!(body_line(content, "org"), 3);
+/// ```
+pub fn body_line(content: &str, extension: &str) -> usize {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let body_start = find_frontmatter(&lines, extension).map_or(0, |b| b.body_start());
+
+    let blank_lines = lines
+        .iter()
+        .skip(body_start)
+        .take_while(|l| l.trim().is_empty())
+        .count();
+
+    (body_start + blank_lines + 1).min(lines.len().max(1))
+}
+
+/// Attempts to locate a front matter block at the start of the lines of a note, in one of the
+/// formats allowed by its extension.
+///
+/// Blocks without a title or identifier, such as the front matter of other tools, are not
+/// considered to be managed by dn and are ignored.
+fn find_frontmatter(lines: &[&str], extension: &str) -> Option<FrontmatterBlock> {
+    let allowed = FrontmatterFormat::allowed_for(extension);
+    let first = lines.first()?.trim_end();
+
+    let block = if let Some((format, delimiter)) = [
+        (FrontmatterFormat::Yaml, "---"),
+        (FrontmatterFormat::Toml, "+++"),
+    ]
+    .into_iter()
+    .find(|&(format, delimiter)| allowed.contains(&format) && first == delimiter)
+    {
+        let end = lines
+            .iter()
+            .skip(1)
+            .position(|l| l.trim_end() == delimiter)?
+            + 1;
+
+        FrontmatterBlock {
+            format,
+            start: 1,
+            end,
+        }
+    } else if allowed.contains(&FrontmatterFormat::Org) {
+        FrontmatterBlock {
+            format: FrontmatterFormat::Org,
+            start: 0,
+            end: lines.iter().take_while(|l| l.starts_with("#+")).count(),
+        }
+    } else if allowed.contains(&FrontmatterFormat::Text) {
+        let end = lines
+            .iter()
+            .position(|l| l.trim_end() == TEXT_FRONTMATTER_SEPARATOR)?;
+        let is_text = lines
+            .iter()
+            .take(end)
+            .all(|l| line_key(FrontmatterFormat::Text, l).is_some());
+
+        if !is_text {
+            return None;
+        }
+
+        FrontmatterBlock {
+            format: FrontmatterFormat::Text,
+            start: 0,
+            end,
+        }
+    } else {
+        return None;
+    };
+
+    let is_managed = lines
+        .iter()
+        .take(block.end)
+        .skip(block.start)
+        .filter_map(|l| line_key(block.format, l).and_then(field_from_key))
+        .any(|f| matches!(f, FrontmatterField::Title | FrontmatterField::Identifier));
+
+    is_managed.then_some(block)
+}
+
+/// Replaces a front matter block with a newly generated one in another format, keeping the body
+/// of the note after the blank lines which separate it from the old block.
+fn convert_frontmatter(
+    lines: &[&str],
+    block: &FrontmatterBlock,
+    frontmatter: &str,
+    newline: &str,
+) -> Result<String, Error> {
+    let mut continuing = false;
+    let has_other_fields =
+        lines.iter().take(block.end).skip(block.start).any(|line| {
+            match line_key(block.format, line).and_then(field_from_key) {
+                Some(_) => {
+                    continuing = true;
+                    false
+                }
+                None if line.trim().is_empty() => false,
+                None => !(continuing && is_continuation(block.format, line)),
+            }
+        });
+
+    if has_other_fields {
+        return Err(anyhow!(
+            "Error converting front matter: The front matter contains fields which dn does not \
+             manage, so it cannot be changed to the format of the new extension"
+        ));
+    }
+
+    let body = lines
+        .iter()
+        .skip(block.body_start())
+        .skip_while(|l| l.trim().is_empty())
+        .copied()
+        .collect::<String>();
+
+    Ok(format!("{}{body}", frontmatter.replace('\n', newline)))
+}
+
+/// Appends the formatted lines for each of the given fields to the output, ensuring the
+/// previous line is terminated first.
+fn push_fields(
+    output: &mut Vec<String>,
+    metadata: &FileMetadata,
+    format: FrontmatterFormat,
+    fields: &[FrontmatterField],
+    newline: &str,
+) {
+    let lines = fields
+        .iter()
+        .filter_map(|&f| format_field(metadata, format, f))
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        return;
+    }
+
+    if output.last().is_some_and(|l| !l.ends_with('\n')) {
+        output.push(newline.to_owned());
+    }
+
+    output.extend(lines.into_iter().map(|l| format!("{l}{newline}")));
+}
+
+/// Returns the key of a front matter line, if it has one.
+///
+/// # Example
+///
+/// ```
+/// let key = line_key(FrontmatterFormat::Org, "#+title: My Note");
+/// assert_eq!(key, Some("title"));
+/// ```
+fn line_key(format: FrontmatterFormat, line: &str) -> Option<&str> {
+    let (key, _) = match format {
+        FrontmatterFormat::Org => line.strip_prefix("#+")?.split_once(':')?,
+        FrontmatterFormat::Toml => line.split_once('=')?,
+        FrontmatterFormat::Yaml | FrontmatterFormat::Text => line.split_once(':')?,
+    };
+
+    let key = key.trim_end();
+    let is_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+
+    is_key.then_some(key)
+}
+
+/// Returns the value of a front matter line exactly as written, if it has one.
+fn raw_value(format: FrontmatterFormat, line: &str) -> Option<String> {
+    line_key(format, line)?;
+
+    let (_, value) = match format {
+        FrontmatterFormat::Toml => line.split_once('=')?,
+        FrontmatterFormat::Org | FrontmatterFormat::Yaml | FrontmatterFormat::Text => {
+            line.split_once(':')?
+        }
+    };
+
+    Some(value.trim().to_owned())
+}
+
+/// Splits a keywords value in any of the front matter formats into its individual keywords.
+///
+/// # Example
+///
+/// ```
+/// let keywords = split_keywords("[\"a\", \"b\"]");
+/// assert_eq!(keywords, vec!["a".to_owned(), "b".to_owned()]);
+/// ```
+fn split_keywords(value: &str) -> Vec<String> {
+    value
+        .split([',', ':', ' ', '\t', '[', ']'])
+        .map(|k| unquote(k.trim_start_matches('-')))
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// Returns the unquoted value of a front matter line, if it has one.
+///
+/// # Example
+///
+/// ```
+/// let value = line_value(FrontmatterFormat::Yaml, "title: \"My Note\"");
+/// assert_eq!(value, Some("My Note".to_owned()));
+/// ```
+fn line_value(format: FrontmatterFormat, line: &str) -> Option<String> {
+    let value = raw_value(format, line)?;
+
+    match format {
+        FrontmatterFormat::Yaml | FrontmatterFormat::Toml => Some(unquote(&value)),
+        FrontmatterFormat::Org | FrontmatterFormat::Text => Some(value),
+    }
+}
+
+/// Returns the field corresponding to a front matter key, if dn manages it.
+fn field_from_key(key: &str) -> Option<FrontmatterField> {
+    match key.to_lowercase().as_str() {
+        "title" => Some(FrontmatterField::Title),
+        "date" => Some(FrontmatterField::Date),
+        "tags" | "filetags" => Some(FrontmatterField::Keywords),
+        "identifier" => Some(FrontmatterField::Identifier),
+        "signature" => Some(FrontmatterField::Signature),
+        _ => None,
+    }
+}
+
+/// Returns whether a line continues the value of the previous field, such as the items of a
+/// multi-line YAML list or TOML array.
+fn is_continuation(format: FrontmatterFormat, line: &str) -> bool {
+    match format {
+        FrontmatterFormat::Yaml | FrontmatterFormat::Toml => {
+            line.starts_with([' ', '\t', '-', ']'])
+        }
+        FrontmatterFormat::Org | FrontmatterFormat::Text => false,
+    }
+}
+
+/// Returns whether an existing title line sanitises to the same title as the metadata.
+fn title_unchanged(
+    format: FrontmatterFormat,
+    line: &str,
+    metadata: &FileMetadata,
+    config: &Config,
+) -> bool {
+    let existing = line_value(format, line);
+    let existing_title = FileMetadata::builder()
+        .with_title(existing.as_deref())
+        .build(config)
+        .title;
+
+    existing_title == metadata.title
+}
+
+/// Returns the key used for a field in the given front matter format.
+fn field_key(format: FrontmatterFormat, field: FrontmatterField) -> &'static str {
+    match (format, field) {
+        (_, FrontmatterField::Title) => "title",
+        (_, FrontmatterField::Date) => "date",
+        (FrontmatterFormat::Org, FrontmatterField::Keywords) => "filetags",
+        (_, FrontmatterField::Keywords) => "tags",
+        (_, FrontmatterField::Identifier) => "identifier",
+        (_, FrontmatterField::Signature) => "signature",
+    }
+}
+
+/// Wraps a value in double quotes, escaping any characters which would terminate the string or
+/// its line.
+///
+/// # Example
+///
+/// ```
+/// let quoted = quote("My \"Note\"");
+/// assert_eq!(quoted, "\"My \\\"Note\\\"\"");
+/// ```
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+
+    format!("\"{escaped}\"")
+}
+
+/// Removes surrounding quotes from a YAML or TOML string value, reversing any escapes.
+///
+/// # Example
+///
+/// ```
+/// let unquoted = unquote("\"My \\\"Note\\\"\"");
+/// assert_eq!(unquoted, "My \"Note\"");
+/// ```
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut unescaped = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                unescaped.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some(escaped) => unescaped.push(escaped),
+                None => unescaped.push('\\'),
+            }
+        }
+
+        unescaped
+    } else if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        inner.replace("''", "'")
+    } else {
+        value.to_owned()
+    }
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_metadata(extension: &str) -> FileMetadata {
+        FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            signature: None,
+            title: Some("my-first-note".to_owned()),
+            title_raw: Some("My First Note".to_owned()),
+            keywords: Some(vec!["demo".to_owned(), "example".to_owned()]),
+            extension: extension.to_owned(),
+        }
+    }
+
+    #[test]
+    fn format_from_extension_respects_markdown_configuration() {
+        // Arrange
+        let config = Config {
+            markdown_frontmatter_format: MarkdownFrontmatterFormat::Toml,
+            ..Default::default()
+        };
+        let test_cases = [
+            ("md", Some(FrontmatterFormat::Toml)),
+            ("org", Some(FrontmatterFormat::Org)),
+            ("txt", Some(FrontmatterFormat::Text)),
+            ("bak.txt", Some(FrontmatterFormat::Text)),
+            ("dj", None),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = FrontmatterFormat::from_extension(input, &config);
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn metadata_to_org_frontmatter() {
+        // Arrange
+        let config = Config::default();
+        let input = setup_metadata("org");
+        let expected = "#+title:      My First Note
+#+date:       [2024-01-01 Mon 12:00]
+#+filetags:   :demo:example:
+#+identifier: 20240101T120000
+
+";
+
+        // Act
+        let result = input.to_frontmatter(&config);
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn metadata_to_text_frontmatter_with_signature() {
+        // Arrange
+        let config = Config::default();
+        let input = FileMetadata {
+            signature: Some("1a1".to_owned()),
+            ..setup_metadata("txt")
+        };
+        let expected = "title:      My First Note
+date:       2024-01-01
+tags:       demo  example
+identifier: 20240101T120000
+signature:  1a1
+---------------------------
+
+";
+
+        // Act
+        let result = input.to_frontmatter(&config);
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn metadata_to_yaml_frontmatter_quotes_values() {
+        // Arrange
+        let config = Config::default();
+        let input = FileMetadata {
+            title_raw: Some("A \"Quoted\" Note".to_owned()),
+            ..setup_metadata("md")
+        };
+        let expected = [
+            "---",
+            "title:      \"A \\\"Quoted\\\" Note\"",
+            "tags:       [\"demo\", \"example\"]",
+            "identifier: \"20240101T120000\"",
+            "---",
+        ];
+
+        // Act
+        #[allow(
+            clippy::unwrap_used,
+            reason = "Markdown always has a front matter format"
+        )]
+        let result = input.to_frontmatter(&config).unwrap();
+        let result_lines = result
+            .lines()
+            .filter(|l| !l.starts_with("date:") && !l.is_empty())
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(expected.to_vec(), result_lines);
+    }
+
+    #[test]
+    fn quote_escapes_line_breaks_and_unquote_reverses_them() {
+        // Arrange
+        let input = "Line \"one\"\r\nC:\\notes\\";
+        let expected = "\"Line \\\"one\\\"\\r\\nC:\\\\notes\\\\\"";
+
+        // Act
+        let quoted = quote(input);
+        let unquoted = unquote(&quoted);
+
+        // Assert
+        assert_eq!(expected, quoted);
+        assert_eq!(input, unquoted);
+    }
+
+    #[test]
+    fn metadata_to_frontmatter_disabled_or_unsupported() {
+        // Arrange
+        let disabled = Config {
+            frontmatter_enabled: false,
+            ..Default::default()
+        };
+        let enabled = Config::default();
+
+        // Act
+        let result_disabled = setup_metadata("md").to_frontmatter(&disabled);
+        let result_unsupported = setup_metadata("dj").to_frontmatter(&enabled);
+
+        // Assert
+        assert_eq!(None, result_disabled);
+        assert_eq!(None, result_unsupported);
+    }
+
+    #[test]
+    fn update_org_frontmatter_preserves_unknown_fields_and_body() {
+        // Arrange
+        let config = Config::default();
+        let metadata = FileMetadata {
+            title: Some("new-title".to_owned()),
+            title_raw: Some("New Title".to_owned()),
+            ..setup_metadata("org")
+        };
+        let input = "#+title:      Old Title
+#+author:     Me
+#+filetags:   :old:
+#+identifier: 20230101T000000
+
+* Heading
+#+title: not front matter
+";
+        let expected = "#+title:      New Title
+#+author:     Me
+#+filetags:   :demo:example:
+#+identifier: 20240101T120000
+
+* Heading
+#+title: not front matter
+";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The front matter keeps its format")]
+        let result = update_frontmatter(input, &metadata.extension, &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn update_yaml_frontmatter_replaces_lists_and_appends_missing_fields() {
+        // Arrange
+        let config = Config::default();
+        let metadata = FileMetadata {
+            signature: Some("1a".to_owned()),
+            ..setup_metadata("md")
+        };
+        let input = "---
+title: \"my first NOTE\"
+draft: true
+tags:
+  - old
+  - older
+---
+
+Body
+";
+        let expected = "---
+title: \"my first NOTE\"
+draft: true
+tags:       [\"demo\", \"example\"]
+identifier: \"20240101T120000\"
+signature:  \"1a\"
+---
+
+Body
+";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The front matter keeps its format")]
+        let result = update_frontmatter(input, &metadata.extension, &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn update_text_frontmatter_removes_absent_signature() {
+        // Arrange
+        let config = Config::default();
+        let metadata = setup_metadata("txt");
+        let input = "title:      My First Note
+tags:       old
+identifier: 20240101T120000
+signature:  1a
+---------------------------
+
+Body";
+        let expected = "title:      My First Note
+tags:       demo  example
+identifier: 20240101T120000
+---------------------------
+
+Body";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The front matter keeps its format")]
+        let result = update_frontmatter(input, &metadata.extension, &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn update_frontmatter_without_block() {
+        // Arrange
+        let config = Config::default();
+        let metadata = setup_metadata("md");
+        let input = "# Heading\n\nBody\n";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The front matter keeps its format")]
+        let result = update_frontmatter(input, &metadata.extension, &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn parse_yaml_frontmatter_fields() {
+        // Arrange
+        let input = "---
+title: \"My \\\"Quoted\\\" Note\"
+tags:
+  - alpha
+  - \"beta\"
+identifier: \"20240101T120000\"
+signature: \"\"
+---
+";
+        let expected = Frontmatter {
+            identifier: Some("20240101T120000".to_owned()),
+            signature: None,
+            title: Some("My \"Quoted\" Note".to_owned()),
+            keywords: Some("alpha beta".to_owned()),
+        };
+
+        // Act
+        let result = parse_frontmatter(input, "md");
+
+        // Assert
+        assert_eq!(Some(expected), result);
+    }
+
+    #[test]
+    fn parse_org_frontmatter_ignores_invalid_identifier() {
+        // Arrange
+        let input = "#+TITLE: Org Note\n#+filetags: :one:two:\n#+identifier: yesterday\n\nBody";
+        let expected = Frontmatter {
+            identifier: None,
+            signature: None,
+            title: Some("Org Note".to_owned()),
+            keywords: Some("one two".to_owned()),
+        };
+
+        // Act
+        let result = parse_frontmatter(input, "org");
+
+        // Assert
+        assert_eq!(Some(expected), result);
+    }
+
+    #[test]
+    fn body_line_skips_frontmatter_and_blank_lines() {
+        // Arrange
+        let test_cases = [
+            ("---\ntitle: Note\n---\n\nBody\n", "md", 5),
+            ("#+title: Note\n#+filetags: :a:\n\nBody", "org", 4),
+            ("title: Note\n---------------------------\n\n", "txt", 3),
+            ("Body without front matter\n", "txt", 1),
+        ];
+
+        for (input, extension, expected) in test_cases {
+            // Act
+            let result = body_line(input, extension);
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn frontmatter_detection_respects_extension() {
+        // Arrange
+        let input = "---\ntitle: Not front matter\n---\n\nBody\n";
+        let metadata = setup_metadata("txt");
+        let config = Config::default();
+
+        // Act
+        let parsed = parse_frontmatter(input, "txt");
+        #[allow(clippy::unwrap_used, reason = "The front matter keeps its format")]
+        let updated = update_frontmatter(input, "txt", &metadata, &config).unwrap();
+        let line = body_line(input, "org");
+
+        // Assert
+        assert_eq!(None, parsed);
+        assert_eq!(None, updated);
+        assert_eq!(1, line);
+    }
+
+    #[test]
+    fn update_frontmatter_ignores_text_without_exact_separator() {
+        // Arrange
+        let input = "TODO: buy milk\n---\nrest\n";
+        let metadata = setup_metadata("txt");
+        let config = Config::default();
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "There is no front matter to convert")]
+        let result = update_frontmatter(input, "txt", &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn update_frontmatter_ignores_blocks_without_managed_keys() {
+        // Arrange
+        let input = "---\nlayout: post\n---\n\nBody\n";
+        let metadata = setup_metadata("md");
+        let config = Config::default();
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "There is no front matter to convert")]
+        let result = update_frontmatter(input, "md", &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn update_frontmatter_converts_to_format_of_new_extension() {
+        // Arrange
+        let input =
+            "---\ntitle: \"My first NOTE\"\ntags: [\"demo\"]\nidentifier: \"20240101T120000\"\n---\n\nBody\n";
+        let metadata = setup_metadata("org");
+        let config = Config::default();
+        let expected = "#+title:      My first NOTE
+#+date:       [2024-01-01 Mon 12:00]
+#+filetags:   :demo:example:
+#+identifier: 20240101T120000
+
+Body
+";
+
+        // Act
+        #[allow(
+            clippy::unwrap_used,
+            reason = "The front matter has only managed fields"
+        )]
+        let result = update_frontmatter(input, "md", &metadata, &config).unwrap();
+
+        // Assert
+        assert_eq!(Some(expected.to_owned()), result);
+    }
+
+    #[test]
+    fn update_frontmatter_refuses_to_convert_unmanaged_fields() {
+        // Arrange
+        let input = "#+title: Old\n#+author: Me\n\nBody\n";
+        let metadata = setup_metadata("md");
+        let config = Config::default();
+
+        // Act
+        let result = update_frontmatter(input, "org", &metadata, &config);
+
+        // Assert
+        assert!(result.is_err(), "Unmanaged fields should not be dropped");
+    }
+}
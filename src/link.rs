@@ -0,0 +1,899 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Data types and utility functions for Denote-style links between notes, which refer to their
+//! targets by identifier.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{anyhow, Error};
+use regex::Regex;
+
+use crate::{
+    config::Config,
+    directory::{is_same_file, read_notes, read_text},
+    filename::{parse_identifier, ToFilename},
+    frontmatter::parse_frontmatter,
+    metadata::identifier_date,
+};
+
+/// Extensions which mark an encrypted file, and which follow the extension of its file type.
+const ENCRYPTION_EXTENSIONS: [&str; 2] = ["gpg", "age"];
+
+/// Matches the `denote:` target of a link in any of the forms used by Denote, capturing the
+/// identifier.
+static REGEX_DENOTE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bdenote:([0-9]{8}T[0-9]{6})\b").expect("Invalid denote link regex pattern")
+});
+
+/// Matches the destination of an inline Markdown link or image, such as `[Title](./note.md)`,
+/// capturing the destination.
+static REGEX_MARKDOWN_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\]\(\s*(<[^<>\n]*>|[^()\s]+)(?:\s+(?:"[^"\n]*"|'[^'\n]*'))?\s*\)"#)
+        .expect("Invalid markdown link regex pattern")
+});
+
+/// Matches a Markdown link reference definition, such as `[title]: ./note.md`, capturing the
+/// destination.
+static REGEX_MARKDOWN_REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^ {0,3}\[[^\]\n]+\]:[ \t]*(<[^<>\n]*>|\S+)")
+        .expect("Invalid markdown reference regex pattern")
+});
+
+/// A `denote:` link found within the content of a note.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DenoteLink {
+    /// The identifier of the note the link points to.
+    pub identifier: String,
+    /// The one-based number of the line containing the link.
+    pub line: usize,
+    /// The byte range of the identifier within the content.
+    pub range: Range<usize>,
+}
+
+/// The changes to links which follow from renaming a note.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Relink<'a> {
+    /// The old and new identifiers of the note, if its identifier changed.
+    pub identifier: Option<(&'a str, &'a str)>,
+    /// The original path and new file name of the note, if its file name changed.
+    pub path: Option<(&'a Path, &'a str)>,
+}
+
+impl Relink<'_> {
+    /// Rewrites the links within the content of the note at `note`, returning the updated
+    /// content and the number of links rewritten.
+    ///
+    /// `denote:` links are rewritten in every note, while relative path links are only
+    /// rewritten in Markdown notes.
+    pub fn apply(&self, note: &Path, content: &str) -> (String, usize) {
+        let format = LinkFormat::from_path(note);
+
+        let (content, identifier_count) = match self.identifier {
+            Some((old, new)) => replace_link_identifier(content, old, new, format),
+            None => (content.to_owned(), 0),
+        };
+
+        let is_markdown = format == LinkFormat::Markdown;
+
+        let (content, path_count) = match self.path {
+            Some((target, new_name)) if is_markdown => {
+                replace_path_links(&content, note, target, new_name)
+            }
+            _ => (content, 0),
+        };
+
+        (content, identifier_count + path_count)
+    }
+}
+
+/// A planned rewrite of the links within a note.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LinkUpdate {
+    /// The path of the note containing the links.
+    pub path: PathBuf,
+    /// The content of the note before its links are rewritten.
+    pub original: String,
+    /// The content of the note after its links are rewritten.
+    pub updated: String,
+    /// The number of links rewritten.
+    pub count: usize,
+}
+
+/// The link syntaxes used by Denote, following the file type of the note containing the link.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LinkFormat {
+    /// `[[denote:IDENTIFIER][Title]]`, used by Org, plain text, and other notes.
+    Org,
+    /// `[Title](denote:IDENTIFIER)`, used by Markdown notes.
+    Markdown,
+}
+
+impl LinkFormat {
+    /// Determines the link format for a file extension, using the Org format for any file type
+    /// which is not Markdown, as Denote does for plain text notes.
+    ///
+    /// Encryption suffixes in a chained extension are skipped, so that `md.gpg` and `md.age`
+    /// notes use the Markdown format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let format = LinkFormat::from_extension("md.gpg");
+    /// assert_eq!(format, LinkFormat::Markdown);
+    /// ```
+    pub fn from_extension(extension: &str) -> Self {
+        let format_extension = extension
+            .trim_start_matches('.')
+            .rsplit('.')
+            .find(|e| !ENCRYPTION_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+
+        match format_extension {
+            Some("md") => Self::Markdown,
+            _ => Self::Org,
+        }
+    }
+
+    /// Determines the link format for the note at a path from everything in its file name after
+    /// the first `.`, as with `from_extension`.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.split_once('.'))
+            .map_or("", |(_, e)| e);
+
+        Self::from_extension(extension)
+    }
+}
+
+/// Formats a link to the note with the given identifier, using the title as the link's
+/// description where one is provided.
+///
+/// Links without a description take the form `[[denote:IDENTIFIER]]` in every format.
+///
+/// # Example
+///
+/// ```
+/// let link = format_link("20240101T120000", Some("My Note"), LinkFormat::Markdown);
+/// assert_eq!(link, "[My Note](denote:20240101T120000)");
+/// ```
+pub fn format_link(identifier: &str, title: Option<&str>, format: LinkFormat) -> String {
+    match (title.filter(|t| !t.is_empty()), format) {
+        (Some(t), LinkFormat::Org) => {
+            // NOTE: An Org description ends at the first `]]`, so a zero-width space is placed
+            // after any `]` which is followed by another or which ends the description.
+            let mut escaped = String::with_capacity(t.len());
+            let mut chars = t.chars().peekable();
+
+            while let Some(c) = chars.next() {
+                escaped.push(c);
+
+                if c == ']' && chars.peek().map_or(true, |n| *n == ']') {
+                    escaped.push('\u{200B}');
+                }
+            }
+
+            format!("[[denote:{identifier}][{escaped}]]")
+        }
+        (Some(t), LinkFormat::Markdown) => {
+            let escaped = t.replace('[', "\\[").replace(']', "\\]");
+            format!("[{escaped}](denote:{identifier})")
+        }
+        _ => format!("[[denote:{identifier}]]"),
+    }
+}
+
+/// Resolves a link target given as a path to a file or as a note identifier, searching for the
+/// note with that identifier within a directory and its subdirectories.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the target is neither an existing file nor an identifier, if no
+/// note has the identifier, or if several files share it.
+///
+/// # Example
+///
+/// ```
+/// let path = resolve_note("20240101T120000", "~/Documents/notes")?;
+/// ```
+pub fn resolve_note<P: AsRef<Path>>(target: &str, directory: P) -> Result<PathBuf, Error> {
+    let path = PathBuf::from(target);
+
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    if identifier_date(target).is_none() {
+        return Err(anyhow!(
+            "{target} is neither an existing file nor a note identifier"
+        ));
+    }
+
+    let mut matches = find_notes(target, directory)?;
+
+    match matches.len() {
+        0 => Err(anyhow!("No note found with identifier {target}")),
+        1 => Ok(matches.remove(0)),
+        _ => Err(anyhow!(
+            "Several files have identifier {target}: {}",
+            matches
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Returns every file within a directory and its subdirectories with the given identifier.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the directory cannot be read.
+pub fn find_notes<P: AsRef<Path>>(identifier: &str, directory: P) -> Result<Vec<PathBuf>, Error> {
+    Ok(read_notes(directory)?
+        .into_iter()
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_identifier)
+                .is_some_and(|i| i == identifier)
+        })
+        .collect())
+}
+
+/// Reads the title of a note for use as a link description, preferring the title in its front
+/// matter and falling back to the title in its file name with hyphens read as spaces.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the note cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let title = note_title(Path::new("20240101T120000--my-note.txt"), &config)?;
+/// assert_eq!(title, Some("my note".to_owned()));
+/// ```
+pub fn note_title(path: &Path, config: &Config) -> Result<Option<String>, Error> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let frontmatter_title = read_text(path)?
+        .as_deref()
+        .and_then(|c| parse_frontmatter(c, extension))
+        .and_then(|f| f.title);
+
+    if frontmatter_title.is_some() {
+        return Ok(frontmatter_title);
+    }
+
+    let filename_title = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.to_owned().to_filename(config).title)
+        .map(|t| t.trim_start_matches("--").replace('-', " "));
+
+    Ok(filename_title)
+}
+
+/// Finds every `denote:` link within the content of a note, in the order they appear.
+///
+/// Links are recognised in their Org (`[[denote:ID][Title]]`), Markdown (`[Title](denote:ID)`),
+/// and plain text (`<denote:ID>`) forms, as well as bare `denote:ID` references. Text within
+/// code is skipped, which is fenced and inline code in Markdown notes, and `#+begin_src` and
+/// `#+begin_example` blocks in other notes.
+///
+/// # Example
+///
+/// ```
+/// let links = find_links("See [[denote:20240101T120000][My Note]].", LinkFormat::Org);
+/// assert_eq!(links[0].identifier, "20240101T120000");
+/// ```
+pub fn find_links(content: &str, format: LinkFormat) -> Vec<DenoteLink> {
+    let code = match format {
+        LinkFormat::Markdown => markdown_code(content),
+        LinkFormat::Org => org_blocks(content),
+    };
+
+    let mut line = 1;
+    let mut counted = 0;
+
+    REGEX_DENOTE_LINK
+        .captures_iter(content)
+        .filter_map(|c| c.get(1))
+        .filter(|m| !code.iter().any(|r| r.contains(&m.start())))
+        .map(|m| {
+            line += content[counted..m.start()].matches('\n').count();
+            counted = m.start();
+
+            DenoteLink {
+                identifier: m.as_str().to_owned(),
+                line,
+                range: m.range(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of the fenced code blocks and inline code spans within Markdown
+/// content. A fenced block which is never closed runs to the end of the content.
+fn markdown_code(content: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut fence: Option<(char, usize, usize)> = None;
+    let mut text_start = 0;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches(' ');
+        let is_indented = line.len() - trimmed.len() > 3;
+        let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+        let run = marker.map_or(0, |m| trimmed.len() - trimmed.trim_start_matches(m).len());
+
+        match (fence, marker) {
+            (None, Some(m)) if !is_indented && run >= 3 => {
+                ranges.extend(inline_code(content, text_start..offset));
+                fence = Some((m, run, offset));
+            }
+            (Some((open, length, start)), Some(m))
+                if !is_indented
+                    && m == open
+                    && run >= length
+                    && trimmed[run..].trim().is_empty() =>
+            {
+                ranges.push(start..offset + line.len());
+                fence = None;
+                text_start = offset + line.len();
+            }
+            _ => {}
+        }
+
+        offset += line.len();
+    }
+
+    match fence {
+        Some((_, _, start)) => ranges.push(start..content.len()),
+        None => ranges.extend(inline_code(content, text_start..content.len())),
+    }
+
+    ranges
+}
+
+/// Returns the byte ranges of the inline code spans within a section of Markdown content, each
+/// opened and closed by runs of the same number of backticks.
+fn inline_code(content: &str, section: Range<usize>) -> Vec<Range<usize>> {
+    let text = &content[section.clone()];
+    let run_length = |at: usize| text[at..].len() - text[at..].trim_start_matches('`').len();
+
+    let mut ranges = Vec::new();
+    let mut position = 0;
+
+    while let Some(found) = text[position..].find('`') {
+        let start = position + found;
+        let length = run_length(start);
+        let mut search = start + length;
+        let mut end = None;
+
+        while let Some(found) = text[search..].find('`') {
+            let close = search + found;
+            let close_length = run_length(close);
+
+            if close_length == length {
+                end = Some(close + close_length);
+                break;
+            }
+
+            search = close + close_length;
+        }
+
+        match end {
+            Some(end) => {
+                ranges.push(section.start + start..section.start + end);
+                position = end;
+            }
+            None => position = start + length,
+        }
+    }
+
+    ranges
+}
+
+/// Returns the byte ranges of the `#+begin_src` and `#+begin_example` blocks within Org content.
+/// A block which is never closed is not a block, as in Org.
+fn org_blocks(content: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut block: Option<(&str, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let directive = line.trim().to_lowercase();
+
+        match block {
+            None => {
+                block = ["src", "example"]
+                    .into_iter()
+                    .find(|kind| {
+                        directive
+                            .strip_prefix("#+begin_")
+                            .and_then(|d| d.strip_prefix(kind))
+                            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+                    })
+                    .map(|kind| (kind, offset));
+            }
+            Some((kind, start)) => {
+                if directive.strip_prefix("#+end_") == Some(kind) {
+                    ranges.push(start..offset + line.len());
+                    block = None;
+                }
+            }
+        }
+
+        offset += line.len();
+    }
+
+    ranges
+}
+
+/// Replaces the identifier of every `denote:` link to `old` within some content with `new`,
+/// returning the updated content and the number of links replaced. Links are found as with
+/// `find_links`, so text within code is left alone.
+///
+/// # Example
+///
+/// ```
+/// let (updated, count) = replace_link_identifier("[[denote:20240101T120000]]", "20240101T120000", "20250101T120000", LinkFormat::Org);
+/// assert_eq!((updated.as_str(), count), ("[[denote:20250101T120000]]", 1));
+/// ```
+pub fn replace_link_identifier(
+    content: &str,
+    old: &str,
+    new: &str,
+    format: LinkFormat,
+) -> (String, usize) {
+    let mut updated = String::with_capacity(content.len());
+    let mut copied = 0;
+    let mut count = 0;
+
+    for link in find_links(content, format)
+        .into_iter()
+        .filter(|l| l.identifier == old)
+    {
+        updated.push_str(&content[copied..link.range.start]);
+        updated.push_str(new);
+        copied = link.range.end;
+        count += 1;
+    }
+
+    updated.push_str(&content[copied..]);
+
+    (updated, count)
+}
+
+/// Plans the rewrite of the links affected by renaming a note, across the text files within a
+/// directory and its subdirectories, skipping the file at `exclude`.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the directory or any of its text files cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let relink = Relink { identifier: Some(("20240101T120000", "20250101T120000")), path: None };
+/// let updates = plan_link_updates("~/Documents/notes", &renamed, &relink)?;
+/// ```
+pub fn plan_link_updates<P: AsRef<Path>>(
+    directory: P,
+    exclude: &Path,
+    relink: &Relink,
+) -> Result<Vec<LinkUpdate>, Error> {
+    let directory = directory.as_ref();
+
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut plan = Vec::new();
+
+    for path in read_notes(directory)? {
+        if is_same_file(&path, exclude) {
+            continue;
+        }
+
+        let Some(original) = read_text(&path)? else {
+            continue;
+        };
+
+        let (updated, count) = relink.apply(&path, &original);
+
+        if count > 0 {
+            plan.push(LinkUpdate {
+                path,
+                original,
+                updated,
+                count,
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Returns the byte ranges of the destinations of the inline links and link reference
+/// definitions within Markdown content, excluding any angle brackets around them.
+fn markdown_destinations(content: &str) -> Vec<Range<usize>> {
+    let mut destinations = REGEX_MARKDOWN_LINK
+        .captures_iter(content)
+        .chain(REGEX_MARKDOWN_REFERENCE.captures_iter(content))
+        .filter_map(|c| c.get(1))
+        .map(|m| {
+            let (start, end) = (m.start(), m.end());
+
+            if m.as_str().starts_with('<') && m.as_str().ends_with('>') && m.len() > 1 {
+                start + 1..end - 1
+            } else {
+                start..end
+            }
+        })
+        .collect::<Vec<_>>();
+
+    destinations.sort_by_key(|r| r.start);
+    destinations.dedup();
+    destinations
+}
+
+/// Replaces the file name of every relative path link from the Markdown note at `note` which
+/// resolves to the file at `target`, returning the updated content and the number of links
+/// replaced.
+///
+/// Any directories and fragment in the link are kept, while links with a URL scheme and
+/// absolute paths are left untouched, as are links within code, as with `find_links`. The target
+/// must still exist at its original path, so this must be called before it is renamed.
+///
+/// # Example
+///
+/// ```
+/// let (updated, count) = replace_path_links(
+///     "[Foo](./20240101T120000--foo.md#intro)",
+///     Path::new("notes/20240102T120000--bar.md"),
+///     Path::new("notes/20240101T120000--foo.md"),
+///     "20240101T120000--new-foo.md",
+/// );
+/// assert_eq!(updated, "[Foo](./20240101T120000--new-foo.md#intro)");
+/// ```
+pub fn replace_path_links(
+    content: &str,
+    note: &Path,
+    target: &Path,
+    new_name: &str,
+) -> (String, usize) {
+    let base = note.parent().unwrap_or_else(|| Path::new(""));
+    let code = markdown_code(content);
+    let mut updated = String::with_capacity(content.len());
+    let mut copied = 0;
+    let mut count = 0;
+
+    for range in markdown_destinations(content) {
+        if code.iter().any(|r| r.contains(&range.start)) {
+            continue;
+        }
+
+        let destination = &content[range.clone()];
+        let path_end = destination.find(['#', '?']).unwrap_or(destination.len());
+        let path = &destination[..path_end];
+
+        let is_relative = !path.is_empty() && !path.starts_with('/') && !path.contains(':');
+
+        if !is_relative || !is_same_file(&base.join(path), target) {
+            continue;
+        }
+
+        let name_start = path.rfind('/').map_or(0, |i| i + 1);
+
+        updated.push_str(&content[copied..range.start]);
+        updated.push_str(&path[..name_start]);
+        updated.push_str(new_name);
+        updated.push_str(&destination[path_end..]);
+        copied = range.end;
+        count += 1;
+    }
+
+    updated.push_str(&content[copied..]);
+
+    (updated, count)
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDirectory;
+
+    #[test]
+    fn format_link_follows_file_type() {
+        // Arrange
+        let identifier = "20240101T120000";
+        let test_cases = [
+            (
+                Some("My Note"),
+                "org",
+                "[[denote:20240101T120000][My Note]]",
+            ),
+            (
+                Some("My [Note]"),
+                "md",
+                "[My \\[Note\\]](denote:20240101T120000)",
+            ),
+            (
+                Some("My Note"),
+                "txt",
+                "[[denote:20240101T120000][My Note]]",
+            ),
+            (
+                Some("Arrays [a]]"),
+                "org",
+                "[[denote:20240101T120000][Arrays [a]\u{200B}]\u{200B}]]",
+            ),
+            (Some("Secret"), "md.gpg", "[Secret](denote:20240101T120000)"),
+            (None, "md", "[[denote:20240101T120000]]"),
+        ];
+
+        for (title, extension, expected) in test_cases {
+            // Act
+            let result = format_link(identifier, title, LinkFormat::from_extension(extension));
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
+    #[test]
+    fn resolve_note_finds_identifier() {
+        // Arrange
+        let directory = TempDirectory::new("link");
+        let note = directory.write("20240101T120000--target__a.md", "");
+        directory.write("20240101T130000--other.md", "");
+
+        // Act
+        let result = resolve_note("20240101T120000", directory.path());
+        let missing = resolve_note("20240101T140000", directory.path());
+        let invalid = resolve_note("not-a-note", directory.path());
+
+        // Assert
+        assert_eq!(Some(note), result.ok());
+        assert!(
+            missing.is_err(),
+            "Expected no note with a missing identifier"
+        );
+        assert!(invalid.is_err(), "Expected no note with an invalid target");
+    }
+
+    #[test]
+    fn find_links_recognises_every_form() {
+        // Arrange
+        let input = "[[denote:20240101T120000][Org]]\n[Md](denote:20240102T120000)\n\n<denote:20240103T120000> and [[denote:20240104T120000]]\ndenote:2024";
+
+        // Act
+        let result = find_links(input, LinkFormat::Org)
+            .into_iter()
+            .map(|l| (l.identifier, l.line))
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(
+            vec![
+                ("20240101T120000".to_owned(), 1),
+                ("20240102T120000".to_owned(), 2),
+                ("20240103T120000".to_owned(), 4),
+                ("20240104T120000".to_owned(), 4),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn find_links_skips_markdown_fenced_code() {
+        // Arrange
+        let input = concat!(
+            "[A](denote:20240101T120000)\n",
+            "```md\n[B](denote:20240102T120000)\n```\n",
+            "~~~~\ndenote:20240103T120000\n~~~\n~~~~\n",
+            "[D](denote:20240104T120000)\n",
+            "```\ndenote:20240105T120000\n",
+        );
+
+        // Act
+        let result = find_links(input, LinkFormat::Markdown)
+            .into_iter()
+            .map(|l| (l.identifier, l.line))
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(
+            vec![
+                ("20240101T120000".to_owned(), 1),
+                ("20240104T120000".to_owned(), 9),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn find_links_skips_markdown_inline_code() {
+        // Arrange
+        let input = "Write `denote:20240101T120000` or ``[B](denote:20240102T120000)`` \
+                     to link to [C](denote:20240103T120000), and ` for denote:20240104T120000";
+
+        // Act
+        let result = find_links(input, LinkFormat::Markdown)
+            .into_iter()
+            .map(|l| l.identifier)
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(
+            vec!["20240103T120000".to_owned(), "20240104T120000".to_owned()],
+            result
+        );
+    }
+
+    #[test]
+    fn find_links_skips_org_source_and_example_blocks() {
+        // Arrange
+        let input = concat!(
+            "[[denote:20240101T120000]]\n",
+            "#+BEGIN_SRC org\n[[denote:20240102T120000]]\n#+END_SRC\n",
+            "  #+begin_example\ndenote:20240103T120000\n#+end_src\n#+end_example\n",
+            "#+begin_quote\n[[denote:20240104T120000]]\n#+end_quote\n",
+            "#+begin_src\n[[denote:20240105T120000]]\n",
+        );
+
+        // Act
+        let result = find_links(input, LinkFormat::Org)
+            .into_iter()
+            .map(|l| l.identifier)
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(
+            vec![
+                "20240101T120000".to_owned(),
+                "20240104T120000".to_owned(),
+                "20240105T120000".to_owned(),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn replace_link_identifier_only_replaces_matching_links() {
+        // Arrange
+        let input =
+            "[[denote:20240101T120000][A]] [B](denote:20240101T120000) [[denote:20240102T120000]]";
+        let expected =
+            "[[denote:20250101T120000][A]] [B](denote:20250101T120000) [[denote:20240102T120000]]";
+
+        // Act
+        let (result, count) =
+            replace_link_identifier(input, "20240101T120000", "20250101T120000", LinkFormat::Org);
+
+        // Assert
+        assert_eq!(expected, result);
+        assert_eq!(2, count);
+    }
+
+    #[test]
+    fn plan_link_updates_skips_excluded_and_unlinked_notes() {
+        // Arrange
+        let directory = TempDirectory::new("plan-links");
+        let target = directory.write(
+            "20240101T120000--target.md",
+            "[Self](denote:20240101T120000)\n",
+        );
+        let markdown = directory.write(
+            "20240102T120000--markdown.md",
+            "[T](denote:20240101T120000) [T](./20240101T120000--target.md)\n",
+        );
+        let org = directory.write(
+            "sub/20240103T120000--org.org",
+            "[[denote:20240101T120000]] [[file:../20240101T120000--target.md]]\n",
+        );
+        directory.write("20240104T120000--unlinked.txt", "denote:20240102T120000\n");
+        let relink = Relink {
+            identifier: Some(("20240101T120000", "20250101T120000")),
+            path: Some((&target, "20250101T120000--target.md")),
+        };
+        let expected = vec![
+            (
+                markdown,
+                "[T](denote:20250101T120000) [T](./20250101T120000--target.md)\n".to_owned(),
+                2,
+            ),
+            (
+                org,
+                "[[denote:20250101T120000]] [[file:../20240101T120000--target.md]]\n".to_owned(),
+                1,
+            ),
+        ];
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The notes were just written")]
+        let result = plan_link_updates(directory.path(), &target, &relink)
+            .unwrap()
+            .into_iter()
+            .map(|u| (u.path, u.updated, u.count))
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn replace_path_links_follows_renamed_file() {
+        // Arrange
+        let directory = TempDirectory::new("path-links");
+        let target = directory.write("20240101T120000--foo.md", "");
+        let note = directory.write("sub/20240102T120000--bar.md", "");
+        let input = concat!(
+            "[Foo](../20240101T120000--foo.md#intro) ",
+            "![img](<../20240101T120000--foo.md> \"Title\") ",
+            "[Web](https://example.com/20240101T120000--foo.md) ",
+            "[Other](./20240101T120000--foo.md)\n",
+            "[ref]: ../20240101T120000--foo.md\n",
+        );
+        let expected = concat!(
+            "[Foo](../20240101T120000--new.md#intro) ",
+            "![img](<../20240101T120000--new.md> \"Title\") ",
+            "[Web](https://example.com/20240101T120000--foo.md) ",
+            "[Other](./20240101T120000--foo.md)\n",
+            "[ref]: ../20240101T120000--new.md\n",
+        );
+
+        // Act
+        let (result, count) = replace_path_links(input, &note, &target, "20240101T120000--new.md");
+
+        // Assert
+        assert_eq!(expected, result);
+        assert_eq!(3, count);
+    }
+
+    #[test]
+    fn replace_path_links_skips_code() {
+        // Arrange
+        let directory = TempDirectory::new("path-links-code");
+        let target = directory.write("20240101T120000--foo.md", "");
+        let note = directory.write("20240102T120000--bar.md", "");
+        let input = concat!(
+            "[Foo](./20240101T120000--foo.md)\n",
+            "```\n",
+            "[Foo](./20240101T120000--foo.md)\n",
+            "```\n",
+            "`[Foo](./20240101T120000--foo.md)`\n",
+        );
+        let expected = concat!(
+            "[Foo](./20240101T120000--new.md)\n",
+            "```\n",
+            "[Foo](./20240101T120000--foo.md)\n",
+            "```\n",
+            "`[Foo](./20240101T120000--foo.md)`\n",
+        );
+
+        // Act
+        let (result, count) = replace_path_links(input, &note, &target, "20240101T120000--new.md");
+
+        // Assert
+        assert_eq!(expected, result);
+        assert_eq!(1, count);
+    }
+}
@@ -5,21 +5,52 @@
 
 //! CLI tool for managing notes in a minimalistic, cross-platform, free, extensible manner.
 
-use std::{fs, path::PathBuf};
+use std::{
+    io::{self, Read},
+    path::PathBuf,
+};
 
 use anyhow::{anyhow, Error};
 use clap::Parser;
-use cli::Cli;
-use config::{load_config, Config};
-use directory::safe_write;
-use filename::ToFilename;
-use metadata::FileMetadata;
+use cli::{Cli, OutputFormat, TemplatesCommands, TitleSource};
+use config::{load_config, Config, ConfigBuilder};
+use directory::{
+    ensure_unmodified, is_same_file, is_within, read_identifiers, read_keywords, read_notes,
+    read_text, safe_rename, safe_replace, safe_write,
+};
+use editor::open_in_editor;
+use filename::{is_conforming, parse_identifier, ToFilename};
+use filter::NoteFilter;
+use frontmatter::{
+    body_line, parse_frontmatter, update_frontmatter, FrontmatterFormat, ToFrontmatter,
+};
+use link::{
+    find_links, format_link, note_title, plan_link_updates, resolve_note, LinkFormat, Relink,
+};
+use metadata::{first_line_title, heading_title, unique_identifier, FileMetadata};
+use output::{
+    diff_lines, DeadLinkRecord, DryRun, LinkRecord, LinkUpdateRecord, NoteRecord, TemplateRecord,
+};
+use prompt::{is_interactive, MetadataAnswers};
+use template::{
+    collect_fields, named_templates, read_template, render_header_value, render_template,
+    select_template,
+};
 
 mod cli;
 mod config;
 mod directory;
+mod editor;
 mod filename;
+mod filter;
+mod frontmatter;
+mod link;
 mod metadata;
+mod output;
+mod prompt;
+mod template;
+#[cfg(test)]
+mod testing;
 
 fn main() -> Result<(), Error> {
     let cli = Cli::parse();
@@ -27,71 +58,229 @@ fn main() -> Result<(), Error> {
     match &cli.command {
         cli::Commands::New {
             cli_print,
+            cli_force,
+            cli_dry_run,
+            cli_interactive,
+            cli_edit,
             cli_directory_path,
             cli_config_path,
             cli_template_path,
+            cli_fields,
+            cli_stdin,
+            cli_title_from,
+            cli_no_frontmatter,
             cli_signature,
             cli_title,
             cli_extension,
             cli_keywords,
         } => {
-            let config = {
-                let mut config_builder = Config::builder();
-
-                let config_base = load_config(cli_config_path.as_deref())
-                    .map_err(|e| anyhow!(e).context("Error loading configuration"))?;
-
-                if let Some(base) = config_base {
-                    config_builder = config_builder.with_base_config(base);
-                }
+            let mut config = {
+                let mut overrides = Config::builder()
+                    .with_frontmatter_disabled(*cli_no_frontmatter)
+                    .with_interactive(*cli_interactive);
 
                 if let Some(path) = cli_directory_path {
-                    config_builder = config_builder.with_file_directory(path.to_owned());
+                    overrides = overrides.with_file_directory(path.to_owned());
                 }
 
                 if let Some(ext) = cli_extension {
-                    config_builder = config_builder.with_file_default_extension(ext.to_owned());
+                    overrides = overrides.with_file_default_extension(ext.to_owned());
                 }
 
                 if let Some(path) = cli_template_path {
-                    config_builder = config_builder.with_file_template_path(PathBuf::from(path));
+                    overrides = overrides.with_file_template_path(PathBuf::from(path));
                 }
 
-                config_builder.build()
+                build_config(cli_config_path.as_deref(), overrides)?
             };
 
-            let metadata = FileMetadata::builder()
-                .with_signature(cli_signature.as_deref())
-                .with_title(cli_title.as_deref())
-                .with_keywords(cli_keywords.as_deref())
-                .with_extension(cli_extension.as_deref())
+            let body = if *cli_stdin {
+                let mut body = Vec::new();
+                io::stdin()
+                    .read_to_end(&mut body)
+                    .map_err(|e| anyhow!(e).context("Error reading standard input"))?;
+
+                body
+            } else {
+                Vec::new()
+            };
+
+            let body_title = cli_title_from.and_then(|source| {
+                let body = String::from_utf8_lossy(&body);
+
+                match source {
+                    TitleSource::Line => first_line_title(&body),
+                    TitleSource::Heading => heading_title(&body),
+                }
+            });
+
+            let mut answers = MetadataAnswers {
+                title: cli_title.clone().or(body_title),
+                keywords: cli_keywords.clone(),
+                signature: cli_signature.clone(),
+                extension: cli_extension.clone(),
+            };
+
+            if config.interactive && is_interactive() {
+                let current = FileMetadata::builder()
+                    .with_extension(cli_extension.as_deref())
+                    .build(&config);
+                let known_keywords = read_keywords(&config.directory)
+                    .map_err(|e| e.context("Error reading existing note keywords"))?;
+
+                answers = answers
+                    .prompt(&current, &known_keywords)
+                    .map_err(|e| e.context("Error reading interactive input"))?;
+            }
+
+            let mut metadata = FileMetadata::builder()
+                .with_signature(answers.signature.as_deref())
+                .with_title(answers.title.as_deref())
+                .with_keywords(answers.keywords.as_deref())
+                .with_extension(answers.extension.as_deref())
                 .build(&config);
 
+            let existing_identifiers = read_identifiers(&config.directory)
+                .map_err(|e| e.context("Error reading existing note identifiers"))?;
+            metadata.identifier = unique_identifier(&metadata.identifier, &existing_identifiers);
+
+            // NOTE: A template given on the command line always takes precedence over the
+            // template rules, which in turn take precedence over the configured default.
+            if cli_template_path.is_none() {
+                if let Some(template) = select_template(&config, &metadata, &config.directory)
+                    .map_err(|e| e.context("Error selecting template"))?
+                {
+                    config.template_path = Some(template);
+                }
+            }
+
+            let template =
+                read_template(&config).map_err(|e| e.context("Error reading template file"))?;
+            let fields = collect_fields(template.as_ref(), cli_fields, is_interactive())
+                .map_err(|e| e.context("Error filling in template fields"))?;
+
+            if let Some(header) = template.as_ref().map(|t| &t.header) {
+                let header_title = header
+                    .title
+                    .as_deref()
+                    .filter(|_| answers.title.is_none())
+                    .map(|t| render_header_value(t, &metadata, &config, &fields));
+                let header_keywords = header
+                    .keywords
+                    .as_deref()
+                    .map(|k| render_header_value(k, &metadata, &config, &fields));
+
+                if header_title.is_some() || header_keywords.is_some() {
+                    let identifier = metadata.identifier;
+
+                    metadata = FileMetadata::builder()
+                        .with_signature(answers.signature.as_deref())
+                        .with_title(header_title.as_deref().or(answers.title.as_deref()))
+                        .with_keywords(answers.keywords.as_deref())
+                        .with_added_keywords(header_keywords.as_deref())
+                        .with_extension(answers.extension.as_deref())
+                        .build(&config);
+                    metadata.identifier = identifier;
+                }
+            }
+
+            // NOTE: Executable templates can have side effects of their own, so a dry run reports
+            // the program it would run instead of running it.
+            let template_run = template
+                .as_ref()
+                .filter(|t| *cli_dry_run && t.executable)
+                .map(|t| t.path.clone());
+
             let filename = metadata.to_filename(&config).to_string();
-            let template = cli_template_path.as_ref().map_or(Ok(Vec::new()), |p| {
-                fs::read(p).map_err(|e| anyhow!(e).context("Error reading template file"))
-            })?;
+            let rendered = render_template(
+                template.as_ref().filter(|_| template_run.is_none()),
+                &metadata,
+                &config,
+                &fields,
+            )
+            .map_err(|e| e.context("Error rendering template"))?;
+
+            for placeholder in &rendered.unrecognised {
+                #[allow(
+                    clippy::print_stderr,
+                    reason = "Unrecognised placeholders are not fatal, but must be reported"
+                )]
+                {
+                    eprintln!("Warning: Leaving unrecognised template placeholder {placeholder}");
+                }
+            }
+
+            let mut content = metadata
+                .to_frontmatter(&config)
+                .unwrap_or_default()
+                .into_bytes()
+                .into_iter()
+                .chain(rendered.content)
+                .collect::<Vec<_>>();
+
+            // NOTE: The body read from standard input follows the template content, starting on
+            // a new line of its own.
+            if !body.is_empty() {
+                if content.last().is_some_and(|&b| b != b'\n') {
+                    content.push(b'\n');
+                }
+
+                content.extend(body);
+            }
 
             let output_path = cli_directory_path
                 .as_ref()
-                .map_or(config.directory, PathBuf::from)
+                .map_or_else(|| config.directory.clone(), PathBuf::from)
                 .join(filename);
 
-            safe_write(&output_path, &template)?;
+            if *cli_dry_run {
+                let record =
+                    NoteRecord::new(&output_path, None, Some(metadata))?.with_dry_run(DryRun {
+                        target_exists: output_path.exists(),
+                        changes: diff_lines("", &String::from_utf8_lossy(&content)),
+                        template_run,
+                    });
+                println!("{}", record.format(cli.cli_format)?);
+
+                return Ok(());
+            }
+
+            safe_write(&output_path, &content, *cli_force)
+                .map_err(|e| e.context("Error creating note"))?;
 
-            if *cli_print {
+            if cli.cli_format != OutputFormat::Text {
+                let record = NoteRecord::new(&output_path, None, Some(metadata))?;
+                println!("{}", record.format(cli.cli_format)?);
+            } else if *cli_print {
                 print!(
                     "{}",
                     output_path
                         .to_str()
                         .ok_or_else(|| anyhow!("Error printing new file path"))?
                 );
-            };
+            }
+
+            if *cli_edit {
+                // NOTE: The cursor is placed on the line after the front matter and template
+                // content, where the note can be continued.
+                let line = content.split(|&b| b == b'\n').count();
+
+                open_in_editor(&config, &output_path, Some(line))
+                    .map_err(|e| e.context("Error opening note in editor"))?;
+            }
         }
         cli::Commands::Rename {
             input,
             cli_print,
+            cli_force,
+            cli_dry_run,
+            cli_interactive,
+            cli_edit,
             cli_regenerate_identifier,
+            cli_update_links,
+            cli_directory_path,
+            cli_no_frontmatter,
+            cli_from_frontmatter,
             cli_config_path,
             cli_signature,
             cli_title,
@@ -101,57 +290,86 @@ fn main() -> Result<(), Error> {
             cli_remove_keywords,
         } => {
             let config = {
-                let mut config_builder = Config::builder();
-
-                let config_base = load_config(cli_config_path.as_deref())
-                    .map_err(|e| anyhow!(e).context("Error loading configuration"))?;
-
-                if let Some(base) = config_base {
-                    config_builder = config_builder.with_base_config(base.clone());
-                }
+                let mut overrides = Config::builder()
+                    .with_file_regenerate_identifier(*cli_regenerate_identifier)
+                    .with_update_links(*cli_update_links)
+                    .with_frontmatter_disabled(*cli_no_frontmatter)
+                    .with_interactive(*cli_interactive);
 
-                if *cli_regenerate_identifier {
-                    config_builder = config_builder.with_file_regenerate_identifier(true);
+                if let Some(path) = cli_directory_path {
+                    overrides = overrides.with_file_directory(path.to_owned());
                 }
 
                 if let Some(ext) = cli_extension {
-                    config_builder = config_builder.with_file_default_extension(ext.to_owned());
+                    overrides = overrides.with_file_default_extension(ext.to_owned());
                 }
 
-                config_builder.build()
+                build_config(cli_config_path.as_deref(), overrides)?
             };
 
             let input_path = PathBuf::from(input);
-            let input_content = fs::read_to_string(&input_path)
-                .map_err(|e| anyhow!(e).context("Error reading input file"))?;
+            // NOTE: Content is only read when a content-aware feature needs it, so that any
+            // kind of file, including binary attachments, can be renamed by its path alone.
+            let mut input_content = if *cli_from_frontmatter {
+                let content = read_text(&input_path)
+                    .map_err(|e| e.context("Error reading input file"))?
+                    .ok_or_else(|| anyhow!("Error reading front matter: Input file is not text"))?;
+
+                Some(content)
+            } else {
+                None
+            };
 
-            let filename_old = PathBuf::from(input)
+            let input_name = PathBuf::from(input)
                 .file_name()
                 .ok_or_else(|| anyhow!("Error reading file name: Could not parse path"))?
                 .to_str()
                 .ok_or_else(|| {
                     anyhow!("Error reading file name: Filename is not in a valid format")
                 })?
-                .to_owned()
-                .to_filename(&config);
+                .to_owned();
 
-            let mut metadata_builder = FileMetadata::builder()
-                .with_identifier(Some(filename_old.identifier).as_deref())
-                .with_signature(filename_old.signature.as_deref())
-                .with_title(filename_old.title.as_deref())
-                .with_keywords(filename_old.keywords.as_deref())
-                .with_extension(Some(filename_old.extension).as_deref());
+            let filename_old = input_name.to_filename(&config);
 
-            if cli_signature.is_some() {
-                metadata_builder = metadata_builder.with_signature(cli_signature.as_deref());
+            let mut metadata_builder = FileMetadata::builder().with_filename(&filename_old);
+
+            if let Some(content) = input_content.as_deref() {
+                let frontmatter =
+                    parse_frontmatter(content, &filename_old.extension).ok_or_else(|| {
+                        anyhow!("Error reading front matter: No front matter found in input file")
+                    })?;
+
+                metadata_builder = frontmatter.apply(metadata_builder);
             }
 
-            if cli_title.is_some() {
-                metadata_builder = metadata_builder.with_title(cli_title.as_deref());
+            let mut answers = MetadataAnswers {
+                title: cli_title.clone(),
+                keywords: cli_keywords.clone(),
+                signature: cli_signature.clone(),
+                extension: cli_extension.clone(),
+            };
+
+            if config.interactive && is_interactive() {
+                let current = metadata_builder.clone().build(&config);
+                let known_keywords =
+                    read_keywords(input_path.parent().unwrap_or(&config.directory))
+                        .map_err(|e| e.context("Error reading existing note keywords"))?;
+
+                answers = answers
+                    .prompt(&current, &known_keywords)
+                    .map_err(|e| e.context("Error reading interactive input"))?;
+            }
+
+            if answers.signature.is_some() {
+                metadata_builder = metadata_builder.with_signature(answers.signature.as_deref());
             }
 
-            if cli_keywords.is_some() {
-                metadata_builder = metadata_builder.with_keywords(cli_keywords.as_deref());
+            if answers.title.is_some() {
+                metadata_builder = metadata_builder.with_title(answers.title.as_deref());
+            }
+
+            if answers.keywords.is_some() {
+                metadata_builder = metadata_builder.with_keywords(answers.keywords.as_deref());
             }
 
             if cli_add_keywords.is_some() {
@@ -164,36 +382,508 @@ fn main() -> Result<(), Error> {
                     metadata_builder.with_removed_keywords(cli_remove_keywords.as_deref());
             }
 
-            if cli_extension.is_some() {
-                metadata_builder = metadata_builder.with_extension(cli_extension.as_deref());
+            if answers.extension.is_some() {
+                metadata_builder = metadata_builder.with_extension(answers.extension.as_deref());
             }
 
-            let metadata = metadata_builder.build(&config);
+            let mut metadata = metadata_builder.build(&config);
+
+            let input_directory = input_path.parent().ok_or_else(|| {
+                anyhow!("Error reading file directory: Could not parse input file parent directory")
+            })?;
+
+            if config.regenerate_identifier || !is_conforming(&input_name) {
+                let existing_identifiers = read_identifiers(input_directory)
+                    .map_err(|e| e.context("Error reading existing note identifiers"))?;
+                metadata.identifier =
+                    unique_identifier(&metadata.identifier, &existing_identifiers);
+            }
 
             let filename_new = metadata.to_filename(&config).to_string();
 
-            let output_path = input_path
-                .parent()
-                .ok_or_else(|| {
-                    anyhow!(
-                        "Error reading file directory: Could not parse input file parent directory"
-                    )
-                })?
-                .join(filename_new);
+            let output_path = input_directory.join(filename_new);
+
+            let has_frontmatter_type = [
+                filename_old.extension.trim_start_matches('.'),
+                metadata.extension.as_str(),
+            ]
+            .iter()
+            .any(|e| FrontmatterFormat::from_extension(e, &config).is_some());
+
+            // NOTE: Links can only point to the old identifier if the note already had one.
+            let relinked_identifier = parse_identifier(&input_name)
+                .filter(|i| config.update_links && *i != metadata.identifier);
+            let relinked_name = output_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .filter(|n| config.update_links && *n != input_name);
+
+            let relink = Relink {
+                identifier: relinked_identifier
+                    .as_deref()
+                    .map(|i| (i, metadata.identifier.as_str())),
+                path: relinked_name.map(|n| (input_path.as_path(), n)),
+            };
+            let needs_relink = relink.identifier.is_some() || relink.path.is_some();
+
+            let needs_content =
+                config.frontmatter_enabled && (has_frontmatter_type || needs_relink);
 
-            fs::rename(&input_path, &output_path)?;
-            safe_write(&output_path, &input_content)?;
+            if input_content.is_none() && needs_content {
+                input_content =
+                    read_text(&input_path).map_err(|e| e.context("Error reading input file"))?;
+            }
+
+            let mut output_content = match input_content
+                .as_deref()
+                .filter(|_| config.frontmatter_enabled)
+            {
+                Some(content) => {
+                    update_frontmatter(content, &filename_old.extension, &metadata, &config)
+                        .map_err(|e| e.context("Error updating front matter"))?
+                }
+                None => None,
+            };
+
+            let link_updates = if needs_relink {
+                // NOTE: Links from the note to itself are rewritten along with its content, which
+                // is left untouched when front matter is disabled.
+                if let Some(content) = output_content
+                    .as_deref()
+                    .or(input_content.as_deref())
+                    .filter(|_| config.frontmatter_enabled)
+                {
+                    let (updated, count) = relink.apply(&input_path, content);
+
+                    if count > 0 {
+                        output_content = Some(updated);
+                    }
+                }
 
-            if *cli_print {
+                if !is_within(&input_path, &config.directory) {
+                    #[allow(
+                        clippy::print_stderr,
+                        reason = "Links left unchanged outside the directory must be reported"
+                    )]
+                    {
+                        eprintln!(
+                            "Warning: {} is not within {}, so only links within that directory \
+                             are updated; use --directory to choose another",
+                            input_path.display(),
+                            config.directory.display()
+                        );
+                    }
+                }
+
+                plan_link_updates(&config.directory, &input_path, &relink)
+                    .map_err(|e| e.context("Error finding links to note"))?
+            } else {
+                Vec::new()
+            };
+
+            // NOTE: JSON output nests the link updates within the note's record, while other
+            // formats report them on standard error, so that each line of standard output is a
+            // single kind of record.
+            let nest_link_updates = cli.cli_format == OutputFormat::Json;
+
+            if *cli_dry_run {
+                let mut update_records = Vec::new();
+
+                for update in &link_updates {
+                    let record = LinkUpdateRecord::new(&update.path, update.count)?
+                        .with_changes(diff_lines(&update.original, &update.updated));
+
+                    if nest_link_updates {
+                        update_records.push(record);
+                    } else {
+                        #[allow(
+                            clippy::print_stderr,
+                            reason = "Link updates are reported apart from the note's record"
+                        )]
+                        {
+                            eprintln!("{}", record.format(cli.cli_format)?);
+                        }
+                    }
+                }
+
+                let changes = match (&input_content, &output_content) {
+                    (Some(original), Some(updated)) => diff_lines(original, updated),
+                    _ => Vec::new(),
+                };
+                let record = NoteRecord::new(&output_path, Some(&input_path), Some(metadata))?
+                    .with_dry_run(DryRun {
+                        target_exists: output_path.exists()
+                            && !is_same_file(&input_path, &output_path),
+                        changes,
+                        template_run: None,
+                    })
+                    .with_link_updates(update_records);
+                println!("{}", record.format(cli.cli_format)?);
+
+                return Ok(());
+            }
+
+            // NOTE: Every note with links to update is checked before anything is changed, so that
+            // a note modified since it was read cannot leave only some of the links rewritten.
+            for update in &link_updates {
+                ensure_unmodified(&update.path, update.original.as_bytes())
+                    .map_err(|e| e.context("Error updating links to note"))?;
+            }
+
+            match (&input_content, &output_content) {
+                (Some(original), Some(updated)) => safe_replace(
+                    &input_path,
+                    &output_path,
+                    original.as_bytes(),
+                    updated.as_bytes(),
+                    *cli_force,
+                ),
+                _ => safe_rename(&input_path, &output_path, *cli_force),
+            }
+            .map_err(|e| e.context("Error renaming note"))?;
+
+            let mut update_records = Vec::new();
+            let mut failed_updates = 0;
+
+            // NOTE: The note has already been renamed, so a failure is reported without stopping,
+            // leaving as few links as possible dangling.
+            for update in &link_updates {
+                let result = safe_replace(
+                    &update.path,
+                    &update.path,
+                    update.original.as_bytes(),
+                    update.updated.as_bytes(),
+                    false,
+                );
+
+                if let Err(e) = result {
+                    failed_updates += 1;
+
+                    #[allow(
+                        clippy::print_stderr,
+                        reason = "Each failed update must be reported before the command fails"
+                    )]
+                    {
+                        eprintln!("Error updating links in {}: {e:#}", update.path.display());
+                    }
+                } else {
+                    let record = LinkUpdateRecord::new(&update.path, update.count)?;
+
+                    if nest_link_updates {
+                        update_records.push(record);
+                    } else {
+                        #[allow(
+                            clippy::print_stderr,
+                            reason = "Link updates are reported apart from the note's record"
+                        )]
+                        {
+                            eprintln!("{}", record.format(cli.cli_format)?);
+                        }
+                    }
+                }
+            }
+
+            if cli.cli_format != OutputFormat::Text {
+                let record = NoteRecord::new(&output_path, Some(&input_path), Some(metadata))?
+                    .with_link_updates(update_records);
+                println!("{}", record.format(cli.cli_format)?);
+            } else if *cli_print {
                 print!(
                     "{}",
                     output_path
                         .to_str()
                         .ok_or_else(|| anyhow!("Error printing new file path"))?
                 );
+            }
+
+            if failed_updates > 0 {
+                return Err(anyhow!(
+                    "Error updating links to note: Failed to update {failed_updates} file(s)"
+                ));
+            }
+
+            if *cli_edit {
+                // NOTE: Rewritten content has front matter in the format of the new extension.
+                let output_extension = output_path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or_default();
+                let line = match (output_content, input_content) {
+                    (Some(content), _) => Some(body_line(&content, output_extension)),
+                    (None, Some(content)) => Some(body_line(&content, &filename_old.extension)),
+                    (None, None) if config.editor_line_argument => read_text(&output_path)
+                        .map_err(|e| e.context("Error reading renamed note"))?
+                        .as_deref()
+                        .map(|c| body_line(c, output_extension)),
+                    (None, None) => None,
+                };
+
+                open_in_editor(&config, &output_path, line)
+                    .map_err(|e| e.context("Error opening note in editor"))?;
+            }
+        }
+        cli::Commands::List {
+            cli_directory_path,
+            cli_config_path,
+            cli_signature,
+            cli_title,
+            cli_keywords,
+            cli_from,
+            cli_to,
+            cli_non_conforming,
+        } => {
+            let config = {
+                let mut overrides = Config::builder();
+
+                if let Some(path) = cli_directory_path {
+                    overrides = overrides.with_file_directory(path.to_owned());
+                }
+
+                build_config(cli_config_path.as_deref(), overrides)?
+            };
+
+            let filter = NoteFilter::builder()
+                .with_signature(cli_signature.as_deref())
+                .with_title(cli_title.as_deref())
+                .with_keywords(cli_keywords.as_deref())
+                .with_from(cli_from.as_deref())
+                .with_to(cli_to.as_deref())
+                .build(&config)?;
+
+            let notes = read_notes(&config.directory)
+                .map_err(|e| e.context("Error reading notes directory"))?;
+
+            let metadata_config = read_only_config(&config);
+
+            for path in notes {
+                let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+
+                let record = if *cli_non_conforming {
+                    if is_conforming(name) {
+                        continue;
+                    }
+
+                    NoteRecord::new(&path, None, None)?
+                } else {
+                    let filename = name.to_owned().to_filename(&config);
+
+                    if !is_conforming(name) || !filter.matches(&filename) {
+                        continue;
+                    }
+
+                    let metadata = FileMetadata::builder()
+                        .with_filename(&filename)
+                        .build(&metadata_config);
+
+                    NoteRecord::new(&path, None, Some(metadata))?
+                };
+
+                println!("{}", record.format(cli.cli_format)?);
+            }
+        }
+        cli::Commands::Link {
+            target,
+            cli_directory_path,
+            cli_config_path,
+            cli_extension,
+            cli_with_title,
+        } => {
+            let config = {
+                let mut overrides = Config::builder();
+
+                if let Some(path) = cli_directory_path {
+                    overrides = overrides.with_file_directory(path.to_owned());
+                }
+
+                if let Some(ext) = cli_extension {
+                    overrides = overrides.with_file_default_extension(ext.to_owned());
+                }
+
+                build_config(cli_config_path.as_deref(), overrides)?
+            };
+
+            let path = resolve_note(target, &config.directory)
+                .map_err(|e| e.context("Error finding note to link to"))?;
+
+            let identifier = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_identifier)
+                .ok_or_else(|| {
+                    anyhow!("Error creating link: {} has no identifier", path.display())
+                })?;
+
+            let title = if *cli_with_title {
+                note_title(&path, &config).map_err(|e| e.context("Error reading note title"))?
+            } else {
+                None
+            };
+
+            let link = format_link(
+                &identifier,
+                title.as_deref(),
+                LinkFormat::from_extension(&config.default_extension),
+            );
+
+            let record = LinkRecord::new(link, &path, &identifier, title)?;
+            println!("{}", record.format(cli.cli_format)?);
+        }
+        cli::Commands::Backlinks {
+            target,
+            cli_directory_path,
+            cli_config_path,
+        } => {
+            let config = {
+                let mut overrides = Config::builder();
+
+                if let Some(path) = cli_directory_path {
+                    overrides = overrides.with_file_directory(path.to_owned());
+                }
+
+                build_config(cli_config_path.as_deref(), overrides)?
             };
+
+            let target_path = resolve_note(target, &config.directory)
+                .map_err(|e| e.context("Error finding linked note"))?;
+
+            let identifier = target_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_identifier)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Error finding backlinks: {} has no identifier",
+                        target_path.display()
+                    )
+                })?;
+
+            let notes = read_notes(&config.directory)
+                .map_err(|e| e.context("Error reading notes directory"))?;
+
+            let metadata_config = read_only_config(&config);
+
+            for path in notes {
+                if is_same_file(&path, &target_path) {
+                    continue;
+                }
+
+                let Some(content) =
+                    read_text(&path).map_err(|e| e.context("Error reading note"))?
+                else {
+                    continue;
+                };
+
+                if !find_links(&content, LinkFormat::from_path(&path))
+                    .iter()
+                    .any(|l| l.identifier == identifier)
+                {
+                    continue;
+                }
+
+                let metadata = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .filter(|n| is_conforming(n))
+                    .map(|n| {
+                        FileMetadata::builder()
+                            .with_filename(&n.to_owned().to_filename(&config))
+                            .build(&metadata_config)
+                    });
+
+                let record = NoteRecord::new(&path, None, metadata)?;
+                println!("{}", record.format(cli.cli_format)?);
+            }
         }
+        cli::Commands::CheckLinks {
+            cli_directory_path,
+            cli_config_path,
+        } => {
+            let config = {
+                let mut overrides = Config::builder();
+
+                if let Some(path) = cli_directory_path {
+                    overrides = overrides.with_file_directory(path.to_owned());
+                }
+
+                build_config(cli_config_path.as_deref(), overrides)?
+            };
+
+            let notes = read_notes(&config.directory)
+                .map_err(|e| e.context("Error reading notes directory"))?;
+            let identifiers = read_identifiers(&config.directory)
+                .map_err(|e| e.context("Error reading existing note identifiers"))?;
+
+            let mut dead_links = 0;
+
+            for path in notes {
+                let Some(content) =
+                    read_text(&path).map_err(|e| e.context("Error reading note"))?
+                else {
+                    continue;
+                };
+
+                for link in find_links(&content, LinkFormat::from_path(&path)) {
+                    if identifiers.contains(&link.identifier) {
+                        continue;
+                    }
+
+                    dead_links += 1;
+
+                    let record = DeadLinkRecord::new(&path, link.line, &link.identifier)?;
+                    println!("{}", record.format(cli.cli_format)?);
+                }
+            }
+
+            if dead_links > 0 {
+                return Err(anyhow!(
+                    "Error checking links: Found {dead_links} dead link(s)"
+                ));
+            }
+        }
+        cli::Commands::Templates { command } => match command {
+            TemplatesCommands::List { cli_config_path } => {
+                let config = build_config(cli_config_path.as_deref(), Config::builder())?;
+
+                let templates = named_templates(&config)
+                    .map_err(|e| e.context("Error reading named templates"))?;
+
+                for template in templates {
+                    let record =
+                        TemplateRecord::new(&template.name, &template.path, template.executable)?;
+                    println!("{}", record.format(cli.cli_format)?);
+                }
+            }
+        },
     }
 
     Ok(())
 }
+
+/// Loads the configuration file, or the default configuration if there is none, and applies the
+/// command line overrides collected in a builder on top of it.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the configuration file cannot be read or parsed.
+fn build_config(cli_config_path: Option<&str>, overrides: ConfigBuilder) -> Result<Config, Error> {
+    let config_base = load_config(cli_config_path)
+        .map_err(|e| anyhow!(e).context("Error loading configuration"))?;
+
+    let config_builder = match config_base {
+        Some(base) => overrides.with_base_config(base),
+        None => overrides,
+    };
+
+    Ok(config_builder.build())
+}
+
+/// Returns a copy of the configuration for reading the metadata of existing notes, which must
+/// never generate new identifiers for them.
+fn read_only_config(config: &Config) -> Config {
+    Config {
+        regenerate_identifier: false,
+        ..config.clone()
+    }
+}
@@ -8,10 +8,11 @@
 
 use std::collections::HashSet;
 
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone};
 use icu_collator::{Collator, CollatorOptions, Strength};
+use serde::Serialize;
 
-use crate::config::Config;
+use crate::{config::Config, filename::Filename};
 
 /// Format string for use with `chrono`'s `format` function.
 /// Represents the structure of a dn `Identifier`.
@@ -22,17 +23,19 @@
 
 /// Represents the deserialised metadata associated with a note that can be encoded
 /// in its title and/or frontmatter.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct FileMetadata {
     pub identifier: String,
     pub signature: Option<String>,
     pub title: Option<String>,
+    #[serde(skip)]
+    pub title_raw: Option<String>,
     pub keywords: Option<Vec<String>>,
     pub extension: String,
 }
 
 /// A `mut self` builder that allows progressively updating an input state for a new `FileMetadata`.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct FileMetadataBuilder {
     identifier: Option<String>,
     signature: Option<String>,
@@ -57,6 +60,23 @@ pub fn with_identifier(mut self, value: Option<&str>) -> Self {
         self
     }
 
+    /// Adds every segment of an existing file name to the builder.
+    ///
+    /// The title is converted back into space-separated words so that it reads naturally if it
+    /// is used anywhere other than the file name, such as in front matter.
+    pub fn with_filename(self, filename: &Filename) -> Self {
+        let title = filename
+            .title
+            .as_ref()
+            .map(|t| t.trim_start_matches('-').replace('-', " "));
+
+        self.with_identifier(Some(filename.identifier.as_str()))
+            .with_signature(filename.signature.as_deref())
+            .with_title(title.as_deref())
+            .with_keywords(filename.keywords.as_deref())
+            .with_extension(Some(filename.extension.as_str()))
+    }
+
     /// Optionally adds a signature to the builder.
     pub fn with_signature(mut self, value: Option<&str>) -> Self {
         self.signature.clone_from(&value.map(String::from));
@@ -129,6 +149,11 @@ pub fn build(&self, config: &Config) -> FileMetadata {
             .as_ref()
             .and_then(|t| parse_title(t, &config.illegal_characters));
 
+        let title_raw = title
+            .as_ref()
+            .and(self.title.as_ref())
+            .map(|t| t.trim().to_owned());
+
         let keywords = {
             let base_keywords = self
                 .keywords
@@ -186,12 +211,132 @@ pub fn build(&self, config: &Config) -> FileMetadata {
             identifier,
             signature,
             title,
+            title_raw,
             keywords,
             extension,
         }
     }
 }
 
+/// Advances an identifier one second at a time until it no longer collides with any of the
+/// existing identifiers, following Denote's approach to resolving identifier conflicts.
+///
+/// Identifiers which cannot be parsed as a date are returned unchanged.
+///
+/// # Example
+///
+/// ```
+/// let existing = HashSet::from(["20240101T120000".to_owned()]);
+/// let identifier = unique_identifier("20240101T120000", &existing);
+/// assert_eq!(identifier, "20240101T120001");
+/// ```
+pub fn unique_identifier(identifier: &str, existing: &HashSet<String>) -> String {
+    let Ok(mut date) = NaiveDateTime::parse_from_str(identifier, DN_IDENTIFIER_FORMAT) else {
+        return identifier.to_owned();
+    };
+
+    let mut candidate = identifier.to_owned();
+
+    while existing.contains(&candidate) {
+        date += TimeDelta::seconds(1);
+        candidate = date.format(DN_IDENTIFIER_FORMAT).to_string();
+    }
+
+    candidate
+}
+
+/// Attempts to interpret a dn identifier as a date and time in the local timezone.
+///
+/// # Example
+///
+/// ```
+/// let date = identifier_date("20240101T120000");
+/// assert!(date.is_some());
+/// ```
+pub fn identifier_date(identifier: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(identifier, DN_IDENTIFIER_FORMAT)
+        .ok()
+        .and_then(|d| Local.from_local_datetime(&d).earliest())
+}
+
+/// Returns the first line of some content which is not blank, to be used as a title.
+///
+/// # Example
+///
+/// ```
+/// let title = first_line_title("\nFix the build\n\nDetails");
+/// assert_eq!(title, Some("Fix the build".to_owned()));
+/// ```
+pub fn first_line_title(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(String::from)
+}
+
+/// Returns the text of the first Markdown or Org heading in some content, to be used as a title.
+///
+/// Markdown headings inside fenced code blocks are ignored, as are the tags of Org headings.
+///
+/// # Example
+///
+/// ```
+/// let title = heading_title("Preamble\n\n## Weekly Review ##\n");
+/// assert_eq!(title, Some("Weekly Review".to_owned()));
+/// ```
+pub fn heading_title(content: &str) -> Option<String> {
+    let mut in_fence = false;
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+
+        if in_fence {
+            continue;
+        }
+
+        let title = markdown_heading(line).or_else(|| org_heading(line));
+
+        if let Some(t) = title.filter(|t| !t.is_empty()) {
+            return Some(t.to_owned());
+        }
+    }
+
+    None
+}
+
+/// Returns the text of a Markdown ATX heading, without its opening or closing `#` markers.
+fn markdown_heading(line: &str) -> Option<&str> {
+    // NOTE: Headings may be indented by up to three spaces.
+    let unindented = line.trim_start_matches(' ');
+    let line = (line.len() - unindented.len() <= 3).then_some(unindented)?;
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix([' ', '\t'])?;
+
+    (1..=6)
+        .contains(&level)
+        .then(|| text.trim().trim_end_matches('#').trim_end())
+}
+
+/// Returns the text of an Org heading, without its stars or tags.
+fn org_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '*').count();
+    let text = line[level..]
+        .strip_prefix(' ')
+        .filter(|_| level > 0)?
+        .trim();
+
+    match text.rsplit_once(char::is_whitespace) {
+        Some((heading, tags)) if tags.len() > 1 && tags.starts_with(':') && tags.ends_with(':') => {
+            Some(heading.trim_end())
+        }
+        _ => Some(text),
+    }
+}
+
 /// Parses the signature argument to a valid dn signature by removing segment separators
 /// and illegal characters, then converting to lowercase. Returns `None` if the result is empty.
 ///
@@ -442,6 +587,7 @@ fn new_metadata_full_integration() {
             identifier: "20241212T121212".to_owned(),
             signature: Some("testsignature".to_owned()),
             title: Some("my-t3st-title".to_owned()),
+            title_raw: Some("My T3ST Title!".to_owned()),
             keywords: Some(vec!["changes".to_owned(), "testing".to_owned()]),
             extension: "dj".to_owned(),
         };
@@ -458,10 +604,34 @@ fn new_metadata_full_integration() {
         );
         assert_eq!(expected.signature, result.signature,);
         assert_eq!(expected.title, result.title,);
+        assert_eq!(expected.title_raw, result.title_raw,);
         assert_eq!(expected.keywords, result.keywords,);
         assert_eq!(expected.extension, result.extension,);
     }
 
+    #[test]
+    fn unique_identifier_skips_existing() {
+        // Arrange
+        let existing = HashSet::from([
+            "20241231T235958".to_owned(),
+            "20241231T235959".to_owned(),
+            "20250101T000000".to_owned(),
+        ]);
+        let test_cases = [
+            ("20241231T235958", "20250101T000001"),
+            ("20241231T235957", "20241231T235957"),
+            ("not-an-identifier", "not-an-identifier"),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = unique_identifier(input, &existing);
+
+            // Assert
+            assert_eq!(expected, result);
+        }
+    }
+
     #[test]
     fn sanitise_with_illegal_chars() {
         // Arrange
@@ -475,4 +645,44 @@ fn sanitise_with_illegal_chars() {
         // Assert
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn heading_title_finds_first_heading() {
+        // Arrange
+        let test_cases = [
+            (
+                "Intro\n\n## Weekly Review ##\n# Later\n",
+                Some("Weekly Review"),
+            ),
+            (
+                "```sh\n# not a heading\n```\n# Real Heading\n",
+                Some("Real Heading"),
+            ),
+            (
+                "#+title: Org\n\n** TODO Plan the trip :travel:work:\n",
+                Some("TODO Plan the trip"),
+            ),
+            ("#hashtag\nNo headings here\n", None),
+        ];
+
+        for (input, expected) in test_cases {
+            // Act
+            let result = heading_title(input);
+
+            // Assert
+            assert_eq!(expected.map(String::from), result);
+        }
+    }
+
+    #[test]
+    fn first_line_title_skips_blank_lines() {
+        // Arrange
+        let input = "\n  \n  Fix the build  \n\nDetails";
+
+        // Act
+        let result = first_line_title(input);
+
+        // Assert
+        assert_eq!(Some("Fix the build".to_owned()), result);
+    }
 }
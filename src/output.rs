@@ -0,0 +1,662 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Machine-readable records describing the notes affected by a command.
+
+use std::path::{self, Path, PathBuf};
+
+use anyhow::{anyhow, Error};
+use serde::Serialize;
+
+use crate::{cli::OutputFormat, metadata::FileMetadata};
+
+/// A description of a single note produced by a command, for consumption by scripts and editor
+/// integrations.
+#[derive(Debug, Serialize)]
+pub struct NoteRecord {
+    /// The absolute path of the note.
+    pub path: PathBuf,
+    /// The absolute path of the note before it was renamed, if it was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<PathBuf>,
+    /// The metadata of the note, if its file name follows the dn naming scheme.
+    #[serde(flatten)]
+    pub metadata: Option<FileMetadata>,
+    /// The planned changes to the note, if the command was a dry run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRun>,
+    /// The other notes whose links to the note were rewritten, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub link_updates: Vec<LinkUpdateRecord>,
+}
+
+/// The changes a command would make to a note, reported instead of being carried out.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DryRun {
+    /// Whether a different file already exists at the note's path.
+    pub target_exists: bool,
+    /// The lines which would be removed from or added to the note, prefixed with `-` or `+`.
+    pub changes: Vec<String>,
+    /// The executable template which would be run to produce the rest of the note's content,
+    /// which is not run during a dry run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_run: Option<PathBuf>,
+}
+
+impl NoteRecord {
+    /// Creates a new record, converting the provided paths into absolute paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the current working directory is required to make a path
+    /// absolute and cannot be determined.
+    pub fn new(
+        path: &Path,
+        old_path: Option<&Path>,
+        metadata: Option<FileMetadata>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            path: path::absolute(path)?,
+            old_path: old_path.map(path::absolute).transpose()?,
+            metadata,
+            dry_run: None,
+            link_updates: Vec::new(),
+        })
+    }
+
+    /// Marks the record as describing a dry run with the given planned changes.
+    pub fn with_dry_run(mut self, value: DryRun) -> Self {
+        self.dry_run = Some(value);
+        self
+    }
+
+    /// Attaches the records of the notes whose links to the note were rewritten.
+    pub fn with_link_updates(mut self, value: Vec<LinkUpdateRecord>) -> Self {
+        self.link_updates = value;
+        self
+    }
+
+    /// Formats the record as a single line in the given output format, without a trailing
+    /// newline. Link updates are only included in the JSON format, as a `link_updates` array.
+    ///
+    /// Dry runs in the text format are instead summarised over several lines: the planned
+    /// operation, whether the target exists, and any changes to the content.
+    ///
+    /// The tab-separated columns are the path, old path, identifier, signature, title, keywords
+    /// (separated by `_`), and extension, with absent values left empty. Dry runs add columns for
+    /// whether the target exists and the number of changed lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the record cannot be serialised.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let record = NoteRecord::new(&path, None, Some(metadata))?;
+    /// println!("{}", record.format(OutputFormat::Json)?);
+    /// ```
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Text => match &self.dry_run {
+                Some(dry_run) => {
+                    let operation = match &self.old_path {
+                        Some(old) => {
+                            format!("rename {} -> {}", old.display(), self.path.display())
+                        }
+                        None => format!("create {}", self.path.display()),
+                    };
+                    let exists = if dry_run.target_exists { "yes" } else { "no" };
+                    let template_run = dry_run
+                        .template_run
+                        .as_ref()
+                        .map(|t| format!("template run: {}", t.display()));
+
+                    Ok([operation, format!("target exists: {exists}")]
+                        .into_iter()
+                        .chain(template_run)
+                        .chain(dry_run.changes.iter().cloned())
+                        .collect::<Vec<_>>()
+                        .join("\n"))
+                }
+                None => Ok(self.path.display().to_string()),
+            },
+            OutputFormat::Json => serde_json::to_string(self)
+                .map_err(|e| anyhow!(e).context("Error serialising output")),
+            OutputFormat::Tsv => {
+                let metadata = self.metadata.clone().unwrap_or_default();
+                let columns = [
+                    self.path.display().to_string(),
+                    self.old_path
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default(),
+                    metadata.identifier,
+                    metadata.signature.unwrap_or_default(),
+                    metadata.title.unwrap_or_default(),
+                    metadata.keywords.unwrap_or_default().join("_"),
+                    metadata.extension,
+                ];
+                let dry_run_columns = self
+                    .dry_run
+                    .iter()
+                    .flat_map(|d| [d.target_exists.to_string(), d.changes.len().to_string()]);
+
+                Ok(columns
+                    .into_iter()
+                    .chain(dry_run_columns)
+                    .map(|c| c.replace(['\t', '\n'], " "))
+                    .collect::<Vec<_>>()
+                    .join("\t"))
+            }
+        }
+    }
+}
+
+/// A description of a template which can be referred to by name.
+#[derive(Debug, Serialize)]
+pub struct TemplateRecord {
+    /// The name of the template.
+    pub name: String,
+    /// The absolute path of the template file.
+    pub path: PathBuf,
+    /// Whether the template is a program whose output becomes the note's content.
+    pub executable: bool,
+}
+
+impl TemplateRecord {
+    /// Creates a new record, converting the provided path into an absolute path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the current working directory is required to make the path
+    /// absolute and cannot be determined.
+    pub fn new(name: &str, path: &Path, executable: bool) -> Result<Self, Error> {
+        Ok(Self {
+            name: name.to_owned(),
+            path: path::absolute(path)?,
+            executable,
+        })
+    }
+
+    /// Formats the record as a single line in the given output format, without a trailing
+    /// newline.
+    ///
+    /// Both the text and tab-separated formats consist of the name and path separated by a tab.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the record cannot be serialised.
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Json => serde_json::to_string(self)
+                .map_err(|e| anyhow!(e).context("Error serialising output")),
+            OutputFormat::Text | OutputFormat::Tsv => {
+                Ok([self.name.clone(), self.path.display().to_string()]
+                    .map(|c| c.replace(['\t', '\n'], " "))
+                    .join("\t"))
+            }
+        }
+    }
+}
+
+/// A link to a note, formatted for inclusion in another note.
+#[derive(Debug, Serialize)]
+pub struct LinkRecord {
+    /// The formatted link.
+    pub link: String,
+    /// The absolute path of the linked note.
+    pub path: PathBuf,
+    /// The identifier of the linked note.
+    pub identifier: String,
+    /// The title used as the link's description, if any.
+    pub title: Option<String>,
+}
+
+impl LinkRecord {
+    /// Creates a new record, converting the provided path into an absolute path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the current working directory is required to make the path
+    /// absolute and cannot be determined.
+    pub fn new(
+        link: String,
+        path: &Path,
+        identifier: &str,
+        title: Option<String>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            link,
+            path: path::absolute(path)?,
+            identifier: identifier.to_owned(),
+            title,
+        })
+    }
+
+    /// Formats the record as a single line in the given output format, without a trailing
+    /// newline.
+    ///
+    /// The text format is the link alone, while the tab-separated format is the link followed by
+    /// the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the record cannot be serialised.
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Text => Ok(self.link.clone()),
+            OutputFormat::Json => serde_json::to_string(self)
+                .map_err(|e| anyhow!(e).context("Error serialising output")),
+            OutputFormat::Tsv => Ok([self.link.clone(), self.path.display().to_string()]
+                .map(|c| c.replace(['\t', '\n'], " "))
+                .join("\t")),
+        }
+    }
+}
+
+/// A link whose identifier does not belong to any note.
+#[derive(Debug, Serialize)]
+pub struct DeadLinkRecord {
+    /// The absolute path of the note containing the link.
+    pub path: PathBuf,
+    /// The one-based number of the line containing the link.
+    pub line: usize,
+    /// The identifier the link points to.
+    pub identifier: String,
+}
+
+impl DeadLinkRecord {
+    /// Creates a new record, converting the provided path into an absolute path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the current working directory is required to make the path
+    /// absolute and cannot be determined.
+    pub fn new(path: &Path, line: usize, identifier: &str) -> Result<Self, Error> {
+        Ok(Self {
+            path: path::absolute(path)?,
+            line,
+            identifier: identifier.to_owned(),
+        })
+    }
+
+    /// Formats the record as a single line in the given output format, without a trailing
+    /// newline.
+    ///
+    /// The text format follows the `path:line: message` convention understood by editors and
+    /// other tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the record cannot be serialised.
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Text => Ok(format!(
+                "{}:{}: denote:{}",
+                self.path.display(),
+                self.line,
+                self.identifier
+            )),
+            OutputFormat::Json => serde_json::to_string(self)
+                .map_err(|e| anyhow!(e).context("Error serialising output")),
+            OutputFormat::Tsv => Ok([
+                self.path.display().to_string(),
+                self.line.to_string(),
+                self.identifier.clone(),
+            ]
+            .map(|c| c.replace(['\t', '\n'], " "))
+            .join("\t")),
+        }
+    }
+}
+
+/// A note whose links have been rewritten to follow another note.
+#[derive(Debug, Serialize)]
+pub struct LinkUpdateRecord {
+    /// The absolute path of the note containing the links.
+    pub path: PathBuf,
+    /// The number of links rewritten.
+    pub links: usize,
+    /// The lines which would be removed or added, if the command was a dry run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<String>>,
+}
+
+impl LinkUpdateRecord {
+    /// Creates a new record, converting the provided path into an absolute path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the current working directory is required to make the path
+    /// absolute and cannot be determined.
+    pub fn new(path: &Path, links: usize) -> Result<Self, Error> {
+        Ok(Self {
+            path: path::absolute(path)?,
+            links,
+            changes: None,
+        })
+    }
+
+    /// Marks the record as describing a dry run with the given planned changes.
+    pub fn with_changes(mut self, value: Vec<String>) -> Self {
+        self.changes = Some(value);
+        self
+    }
+
+    /// Formats the record in the given output format, without a trailing newline.
+    ///
+    /// The text format describes the update in a sentence, followed by the planned changes on
+    /// separate lines for a dry run. The tab-separated columns are the path and number of links.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the record cannot be serialised.
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Text => {
+                let verb = if self.changes.is_some() {
+                    "update"
+                } else {
+                    "updated"
+                };
+                let summary = format!("{verb} {} link(s) in {}", self.links, self.path.display());
+
+                Ok(std::iter::once(summary)
+                    .chain(self.changes.iter().flatten().cloned())
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            OutputFormat::Json => serde_json::to_string(self)
+                .map_err(|e| anyhow!(e).context("Error serialising output")),
+            OutputFormat::Tsv => Ok([self.path.display().to_string(), self.links.to_string()]
+                .map(|c| c.replace(['\t', '\n'], " "))
+                .join("\t")),
+        }
+    }
+}
+
+/// Compares two versions of a text line by line, returning the lines removed from the old
+/// version prefixed with `-` and the lines added in the new version prefixed with `+`.
+///
+/// # Example
+///
+/// ```
+/// let changes = diff_lines("a\nb\nc", "a\nB\nc");
+/// assert_eq!(changes, vec!["-b".to_owned(), "+B".to_owned()]);
+/// ```
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    // NOTE: Only the differing middle section is compared, since dn only ever changes a small
+    // part of a note and a full comparison of large notes would be needlessly expensive.
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines
+        .iter()
+        .skip(prefix)
+        .rev()
+        .zip(new_lines.iter().skip(prefix).rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = old_lines
+        .get(prefix..old_lines.len() - suffix)
+        .unwrap_or_default();
+    let new_middle = new_lines
+        .get(prefix..new_lines.len() - suffix)
+        .unwrap_or_default();
+
+    // Longest common subsequence lengths of each pair of suffixes of the two sections.
+    let mut lengths = vec![vec![0_usize; new_middle.len() + 1]; old_middle.len() + 1];
+
+    for (i, old_line) in old_middle.iter().enumerate().rev() {
+        for (j, new_line) in new_middle.iter().enumerate().rev() {
+            let length = if old_line == new_line {
+                lcs_length(&lengths, i + 1, j + 1) + 1
+            } else {
+                lcs_length(&lengths, i + 1, j).max(lcs_length(&lengths, i, j + 1))
+            };
+
+            if let Some(cell) = lengths.get_mut(i).and_then(|row| row.get_mut(j)) {
+                *cell = length;
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old_middle.len() || j < new_middle.len() {
+        match (old_middle.get(i), new_middle.get(j)) {
+            (Some(a), Some(b)) if a == b => {
+                i += 1;
+                j += 1;
+            }
+            (Some(a), Some(_))
+                if lcs_length(&lengths, i + 1, j) >= lcs_length(&lengths, i, j + 1) =>
+            {
+                changes.push(format!("-{a}"));
+                i += 1;
+            }
+            (Some(a), None) => {
+                changes.push(format!("-{a}"));
+                i += 1;
+            }
+            (_, Some(b)) => {
+                changes.push(format!("+{b}"));
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+
+    changes
+}
+
+/// Returns a cell of the longest common subsequence table, treating cells beyond the end of
+/// either section as zero.
+fn lcs_length(lengths: &[Vec<usize>], i: usize, j: usize) -> usize {
+    lengths
+        .get(i)
+        .and_then(|row| row.get(j))
+        .copied()
+        .unwrap_or_default()
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_record() -> NoteRecord {
+        NoteRecord {
+            path: PathBuf::from("/notes/20240101T120000==1a--title__a_b.md"),
+            old_path: Some(PathBuf::from("/notes/title.md")),
+            metadata: Some(FileMetadata {
+                identifier: "20240101T120000".to_owned(),
+                signature: Some("1a".to_owned()),
+                title: Some("title".to_owned()),
+                title_raw: Some("Title".to_owned()),
+                keywords: Some(vec!["a".to_owned(), "b".to_owned()]),
+                extension: "md".to_owned(),
+            }),
+            dry_run: None,
+            link_updates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn record_formats_as_json() {
+        // Arrange
+        let input = setup_record();
+        let expected = concat!(
+            r#"{"path":"/notes/20240101T120000==1a--title__a_b.md","#,
+            r#""old_path":"/notes/title.md","identifier":"20240101T120000","#,
+            r#""signature":"1a","title":"title","keywords":["a","b"],"extension":"md"}"#
+        );
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The record is always serialisable")]
+        let result = input.format(OutputFormat::Json).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn record_formats_link_updates_as_json() {
+        // Arrange
+        let input = NoteRecord {
+            old_path: None,
+            metadata: None,
+            ..setup_record()
+        }
+        .with_link_updates(vec![LinkUpdateRecord {
+            path: PathBuf::from("/notes/20240102T120000--other.md"),
+            links: 2,
+            changes: None,
+        }]);
+        let expected = concat!(
+            r#"{"path":"/notes/20240101T120000==1a--title__a_b.md","#,
+            r#""link_updates":[{"path":"/notes/20240102T120000--other.md","links":2}]}"#
+        );
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The record is always serialisable")]
+        let result = input.format(OutputFormat::Json).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn record_formats_as_tsv_with_empty_columns() {
+        // Arrange
+        let input = NoteRecord {
+            old_path: None,
+            metadata: None,
+            ..setup_record()
+        };
+        let expected = "/notes/20240101T120000==1a--title__a_b.md\t\t\t\t\t\t";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "TSV formatting cannot fail")]
+        let result = input.format(OutputFormat::Tsv).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn dry_run_record_formats_as_tsv_with_dry_run_columns() {
+        // Arrange
+        let input = setup_record().with_dry_run(DryRun {
+            target_exists: true,
+            changes: vec!["-old".to_owned(), "+new".to_owned()],
+            template_run: None,
+        });
+        let expected = "/notes/20240101T120000==1a--title__a_b.md\t/notes/title.md\t\
+                        20240101T120000\t1a\ttitle\ta_b\tmd\ttrue\t2";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "TSV formatting cannot fail")]
+        let result = input.format(OutputFormat::Tsv).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn dead_link_record_formats_as_text_location() {
+        // Arrange
+        let input = DeadLinkRecord {
+            path: PathBuf::from("/notes/20240101T120000--title.org"),
+            line: 12,
+            identifier: "20230101T120000".to_owned(),
+        };
+        let expected = "/notes/20240101T120000--title.org:12: denote:20230101T120000";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "Text formatting cannot fail")]
+        let result = input.format(OutputFormat::Text).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn dry_run_record_formats_as_text_summary() {
+        // Arrange
+        let input = setup_record().with_dry_run(DryRun {
+            target_exists: false,
+            changes: vec!["-old".to_owned(), "+new".to_owned()],
+            template_run: None,
+        });
+        let expected = "rename /notes/title.md -> /notes/20240101T120000==1a--title__a_b.md
+target exists: no
+-old
++new";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "Text formatting cannot fail")]
+        let result = input.format(OutputFormat::Text).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn dry_run_record_formats_template_run_as_text() {
+        // Arrange
+        let input = NoteRecord {
+            old_path: None,
+            ..setup_record()
+        }
+        .with_dry_run(DryRun {
+            target_exists: false,
+            changes: vec!["+title: Title".to_owned()],
+            template_run: Some(PathBuf::from("/templates/daily.sh")),
+        });
+        let expected = "create /notes/20240101T120000==1a--title__a_b.md
+target exists: no
+template run: /templates/daily.sh
++title: Title";
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "Text formatting cannot fail")]
+        let result = input.format(OutputFormat::Text).unwrap();
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn diff_lines_reports_only_changes() {
+        // Arrange
+        let old = "#+title: Old\n#+author: Me\n#+filetags: :a:\n\nBody\n";
+        let new = "#+title: New\n#+author: Me\n#+filetags: :a:b:\n#+signature: 1\n\nBody\n";
+        let expected = vec![
+            "-#+title: Old".to_owned(),
+            "+#+title: New".to_owned(),
+            "-#+filetags: :a:".to_owned(),
+            "+#+filetags: :a:b:".to_owned(),
+            "+#+signature: 1".to_owned(),
+        ];
+
+        // Act
+        let result = diff_lines(old, new);
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Prompts for reading values from the user on the terminal.
+
+use std::io::{self, IsTerminal};
+
+use anyhow::{anyhow, Error};
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Behavior, Context, Editor, Helper,
+};
+
+use crate::metadata::FileMetadata;
+
+/// Values for the metadata of a note, either given on the command line or entered interactively.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MetadataAnswers {
+    pub title: Option<String>,
+    pub keywords: Option<String>,
+    pub signature: Option<String>,
+    pub extension: Option<String>,
+}
+
+impl MetadataAnswers {
+    /// Prompts in turn for the title, keywords, signature and extension of a note, skipping any
+    /// value which has already been given. Each prompt is pre-filled with the current value from
+    /// `current`, and keywords are completed from `known_keywords`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `anyhow::Error` if the terminal cannot be read from, or if the user cancels a
+    /// prompt.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let answers = MetadataAnswers::default().prompt(&metadata, &["work".to_owned()])?;
+    /// ```
+    pub fn prompt(self, current: &FileMetadata, known_keywords: &[String]) -> Result<Self, Error> {
+        let title = match self.title {
+            Some(t) => t,
+            None => prompt(
+                "Title",
+                current.title_raw.as_deref().unwrap_or_default(),
+                &[],
+            )?,
+        };
+
+        let keywords = match self.keywords {
+            Some(k) => k,
+            None => prompt(
+                "Keywords",
+                &current.keywords.as_deref().unwrap_or_default().join(" "),
+                known_keywords,
+            )?,
+        };
+
+        let signature = match self.signature {
+            Some(s) => s,
+            None => prompt(
+                "Signature",
+                current.signature.as_deref().unwrap_or_default(),
+                &[],
+            )?,
+        };
+
+        let extension = match self.extension {
+            Some(e) => e,
+            None => prompt("Extension", &current.extension, &[])?,
+        };
+
+        Ok(Self {
+            title: Some(title),
+            keywords: Some(keywords),
+            signature: Some(signature),
+            extension: Some(extension).filter(|e| !e.trim().is_empty()),
+        })
+    }
+}
+
+/// Completes the word under the cursor from a list of candidates.
+struct WordCompleter<'a> {
+    candidates: &'a [String],
+}
+
+impl Completer for WordCompleter<'_> {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, word) = word_before(line, pos);
+        let matches = complete_word(word, self.candidates)
+            .map(|c| Pair {
+                display: c.to_owned(),
+                replacement: c.to_owned(),
+            })
+            .collect();
+
+        Ok((start, matches))
+    }
+}
+
+impl Hinter for WordCompleter<'_> {
+    type Hint = String;
+}
+
+impl Highlighter for WordCompleter<'_> {}
+
+impl Validator for WordCompleter<'_> {}
+
+impl Helper for WordCompleter<'_> {}
+
+/// Returns the start position and text of the word which ends at `pos` in `line`. Words are
+/// separated by spaces, commas and underscores, as keywords are.
+fn word_before(line: &str, pos: usize) -> (usize, &str) {
+    let before = &line[..pos];
+    let start = before.rfind([' ', ',', '_']).map_or(0, |i| i + 1);
+
+    (start, &before[start..])
+}
+
+/// Returns the candidates which begin with `word`.
+fn complete_word<'a>(word: &'a str, candidates: &'a [String]) -> impl Iterator<Item = &'a str> {
+    candidates
+        .iter()
+        .map(String::as_str)
+        .filter(move |c| c.starts_with(word))
+}
+
+/// Reads a line of input on the terminal, pre-filled with `initial` and with tab completion from
+/// `completions`.
+///
+/// The prompt is written to the terminal rather than standard output, so that it never mixes with
+/// the command's output.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the terminal cannot be read from, or if the user cancels the
+/// prompt.
+///
+/// # Example
+///
+/// ```
+/// let mood = prompt("How are you feeling?", "calm", &[])?;
+/// ```
+pub fn prompt(message: &str, initial: &str, completions: &[String]) -> Result<String, Error> {
+    let config = rustyline::Config::builder()
+        .behavior(Behavior::PreferTerm)
+        .auto_add_history(false)
+        .build();
+
+    let mut editor: Editor<WordCompleter, DefaultHistory> = Editor::with_config(config)?;
+    editor.set_helper(Some(WordCompleter {
+        candidates: completions,
+    }));
+
+    match editor.readline_with_initial(&format!("{message}: "), (initial, "")) {
+        Ok(line) => Ok(line.trim().to_owned()),
+        Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
+            Err(anyhow!("No value entered for \"{message}\""))
+        }
+        Err(e) => Err(anyhow!(e)),
+    }
+}
+
+/// Returns whether dn is attached to a terminal on which the user can be prompted.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_before_splits_on_keyword_separators() {
+        // Arrange
+        let line = "work_meet";
+
+        // Act
+        let (start, word) = word_before(line, line.len());
+
+        // Assert
+        assert_eq!(start, 5);
+        assert_eq!(word, "meet");
+    }
+
+    #[test]
+    fn complete_word_matches_prefixes() {
+        // Arrange
+        let candidates = vec!["meeting".to_owned(), "memo".to_owned(), "work".to_owned()];
+
+        // Act
+        let matches = complete_word("me", &candidates).collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(matches, vec!["meeting", "memo"]);
+    }
+}
@@ -0,0 +1,1237 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Resolution and loading of the templates applied to new notes.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{self, Path, PathBuf},
+    process::{Command, Stdio},
+    sync::LazyLock,
+};
+
+use anyhow::{anyhow, Error};
+use chrono::format::{Item, StrftimeItems};
+use minijinja::{context, path_loader, Environment, ErrorKind, UndefinedBehavior, Value};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, TemplateEntry},
+    directory::expand_path,
+    metadata::{identifier_date, FileMetadata},
+    prompt::prompt,
+};
+
+/// The names of the variables provided by dn, which template fields cannot use.
+const RESERVED_VARIABLES: [&str; 10] = [
+    "identifier",
+    "title",
+    "title_slug",
+    "keywords",
+    "signature",
+    "extension",
+    "date",
+    "config",
+    "date_format",
+    "env",
+];
+
+/// The line which opens the header of a template.
+const TEMPLATE_HEADER_OPEN: &str = "--- dn";
+
+/// The line which closes the header of a template.
+const TEMPLATE_HEADER_CLOSE: &str = "---";
+
+/// Regex to match a `{{variable}}` placeholder in a plain template.
+static REGEX_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*([^{}]*?)\s*\}\}").expect("Invalid placeholder regex pattern")
+});
+
+/// A template read from disk, separated into its header and body.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Template {
+    /// The expanded path to the template file.
+    pub path: PathBuf,
+    /// Whether the template is a program whose output becomes the note's content, in which case
+    /// it has neither a header nor a body.
+    pub executable: bool,
+    /// The settings declared in the template's header.
+    pub header: TemplateHeader,
+    /// The content of the template after its header.
+    pub body: Vec<u8>,
+}
+
+/// The settings a template can declare for itself in its header.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct TemplateHeader {
+    /// The language in which the template is written.
+    pub engine: Option<HeaderEngine>,
+    /// The fields which must be filled in before the template is rendered, in prompting order.
+    #[serde(default)]
+    pub fields: Vec<TemplateField>,
+    /// A title for the note, which may contain field placeholders, used when none is provided.
+    pub title: Option<String>,
+    /// Keywords to add to the note, which may contain field placeholders.
+    pub keywords: Option<String>,
+}
+
+/// A named value which a template asks for before it is rendered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateField {
+    /// The name by which the field is referred to in the template.
+    pub name: String,
+    /// The question to ask when prompting for the field, which defaults to its name.
+    pub prompt: Option<String>,
+    /// The value to use when none is entered or provided.
+    pub default: Option<String>,
+}
+
+/// The languages which a template can declare in its header. Executable templates are only
+/// enabled through the configuration file, so they cannot be declared here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderEngine {
+    /// Plain text with `{{variable}}` placeholders.
+    Simple,
+    /// The Jinja template language.
+    Jinja,
+}
+
+impl From<HeaderEngine> for TemplateEngine {
+    fn from(engine: HeaderEngine) -> Self {
+        match engine {
+            HeaderEngine::Simple => Self::Simple,
+            HeaderEngine::Jinja => Self::Jinja,
+        }
+    }
+}
+
+/// The languages in which a template can be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateEngine {
+    /// Plain text with `{{variable}}` placeholders.
+    Simple,
+    /// The Jinja template language.
+    Jinja,
+    /// A program whose output becomes the note's content.
+    Executable,
+}
+
+impl Template {
+    /// Determines the language of the template from its header, or otherwise from its
+    /// extension, where `.jinja` and `.j2` indicate Jinja. Executable templates are always run.
+    pub fn engine(&self) -> TemplateEngine {
+        if self.executable {
+            return TemplateEngine::Executable;
+        }
+
+        self.header.engine.map_or_else(
+            || match self.path.extension().and_then(|e| e.to_str()) {
+                Some("jinja" | "j2") => TemplateEngine::Jinja,
+                _ => TemplateEngine::Simple,
+            },
+            TemplateEngine::from,
+        )
+    }
+}
+
+/// A template file, along with the name or path by which it is referred to.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NamedTemplate {
+    /// The name of the template.
+    pub name: String,
+    /// The expanded path to the template file.
+    pub path: PathBuf,
+    /// Whether the template is a program whose output becomes the note's content.
+    pub executable: bool,
+    /// The fields declared for the template in the configuration file.
+    pub fields: Vec<TemplateField>,
+}
+
+/// Resolves the template of a `Config` to the file it refers to.
+///
+/// The template is first looked up by name in the `templates` table, then among the files of
+/// the template directory, and is otherwise treated as a path. Paths may use the home indicator,
+/// "~", and environment variables.
+///
+/// Returns `None` when no template has been provided on the command line or in the
+/// configuration file.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if a path cannot be expanded, or if the template does not refer
+/// to an existing file.
+///
+/// # Example
+///
+/// ```
+/// let config = Config {
+///     template_path: Some(PathBuf::from("journal")),
+///     templates: BTreeMap::from([(
+///         "journal".to_owned(),
+///         TemplateEntry::Path(PathBuf::from("~/journal.txt")),
+///     )]),
+///     ..Default::default()
+/// };
+/// let template = resolve_template(&config)?.unwrap();
+/// assert_eq!(template.path, PathBuf::from("/home/me/journal.txt"));
+/// ```
+pub fn resolve_template(config: &Config) -> Result<Option<NamedTemplate>, Error> {
+    let Some(template) = config.template_path.as_ref() else {
+        return Ok(None);
+    };
+
+    let resolved = match find_named_template(template, config)? {
+        Some(named) => named,
+        None => NamedTemplate {
+            name: template.display().to_string(),
+            path: expand_path(template)?,
+            executable: false,
+            fields: Vec::new(),
+        },
+    };
+
+    if !resolved.path.is_file() {
+        return Err(anyhow!(
+            "Template {} is neither a named template nor an existing file",
+            template.display()
+        ));
+    }
+
+    Ok(Some(resolved))
+}
+
+/// Selects the template of the first rule in a `Config` which matches a new note's metadata and
+/// the directory in which it will be created.
+///
+/// The criteria of each rule are sanitised in the same way as the note's metadata, so that
+/// `keywords = ["Meeting"]` matches a note created with `--keywords meeting`.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the directory of a rule or the note cannot be expanded into an
+/// absolute path.
+///
+/// # Example
+///
+/// ```
+/// let config = Config {
+///     template_rules: vec![TemplateRule {
+///         template: PathBuf::from("meeting"),
+///         keywords: Some(vec!["meeting".to_owned()]),
+///         extension: None,
+///         signature: None,
+///         directory: None,
+///     }],
+///     ..Default::default()
+/// };
+/// let template = select_template(&config, &metadata, &config.directory)?;
+/// assert_eq!(template, Some(PathBuf::from("meeting")));
+/// ```
+pub fn select_template(
+    config: &Config,
+    metadata: &FileMetadata,
+    directory: &Path,
+) -> Result<Option<PathBuf>, Error> {
+    let note_directory = path::absolute(expand_path(directory)?)?;
+    let note_keywords = metadata.keywords.clone().unwrap_or_default();
+
+    for rule in &config.template_rules {
+        let criteria = FileMetadata::builder()
+            .with_keywords(rule.keywords.as_ref().map(|k| k.join(" ")).as_deref())
+            .with_signature(rule.signature.as_deref())
+            .build(config);
+        let extension = rule
+            .extension
+            .as_deref()
+            .map(|e| e.trim_start_matches('.').to_lowercase());
+
+        let keywords_match = criteria
+            .keywords
+            .map_or(true, |ks| ks.iter().all(|k| note_keywords.contains(k)));
+        let extension_match = extension.map_or(true, |e| e == metadata.extension);
+        let signature_match = criteria
+            .signature
+            .map_or(true, |s| metadata.signature.as_ref() == Some(&s));
+        let directory_match = match rule.directory.as_ref() {
+            Some(d) => note_directory.starts_with(path::absolute(expand_path(d)?)?),
+            None => true,
+        };
+
+        if keywords_match && extension_match && signature_match && directory_match {
+            return Ok(Some(rule.template.clone()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Collects every template which can be referred to by name, sorted by name.
+///
+/// Files in the template directory are named by their file stem, and entries in the `templates`
+/// table take precedence over them.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if a path cannot be expanded or the template directory cannot be
+/// read.
+pub fn named_templates(config: &Config) -> Result<Vec<NamedTemplate>, Error> {
+    let mut templates = directory_templates(config)?
+        .into_iter()
+        .map(|t| (t.name.clone(), t))
+        .collect::<BTreeMap<_, _>>();
+
+    for (name, entry) in &config.templates {
+        templates.insert(name.to_owned(), registry_template(name, entry, config)?);
+    }
+
+    Ok(templates.into_values().collect())
+}
+
+/// Looks up a template by name, returning `None` if the name is not known or is a path.
+fn find_named_template(template: &Path, config: &Config) -> Result<Option<NamedTemplate>, Error> {
+    let Some(name) = template.to_str() else {
+        return Ok(None);
+    };
+
+    if let Some(entry) = config.templates.get(name) {
+        return registry_template(name, entry, config).map(Some);
+    }
+
+    // NOTE: Only bare names are looked up in the template directory, so that paths such as
+    // "./journal" always refer to the file they name.
+    if template.file_name() != Some(template.as_os_str()) {
+        return Ok(None);
+    }
+
+    Ok(directory_templates(config)?
+        .into_iter()
+        .find(|t| t.name == name))
+}
+
+/// Resolves an entry in the `templates` table, expanding its path and resolving relative paths
+/// against the template directory when one is configured.
+fn registry_template(
+    name: &str,
+    entry: &TemplateEntry,
+    config: &Config,
+) -> Result<NamedTemplate, Error> {
+    let expanded = expand_path(entry.path())?;
+
+    let path = match config.template_directory.as_ref() {
+        Some(directory) if expanded.is_relative() => expand_path(directory)?.join(expanded),
+        _ => expanded,
+    };
+
+    Ok(NamedTemplate {
+        name: name.to_owned(),
+        path,
+        executable: entry.is_executable(),
+        fields: entry.fields().to_vec(),
+    })
+}
+
+/// Collects the files in the template directory, named by their file stems and skipping hidden
+/// files.
+fn directory_templates(config: &Config) -> Result<Vec<NamedTemplate>, Error> {
+    let Some(directory) = config.template_directory.as_ref() else {
+        return Ok(Vec::new());
+    };
+
+    let directory = expand_path(directory)?;
+    let entries = fs::read_dir(&directory).map_err(|e| {
+        anyhow!(e).context(format!(
+            "Unable to read template directory {}",
+            directory.display()
+        ))
+    })?;
+
+    let mut templates = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+
+        let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        if name.starts_with('.') || !path.is_file() {
+            continue;
+        }
+
+        templates.push(NamedTemplate {
+            name: name.to_owned(),
+            path,
+            executable: false,
+            fields: Vec::new(),
+        });
+    }
+
+    Ok(templates)
+}
+
+/// Reads the template of a `Config`, separating its header from its body. Returns `None` when no
+/// template has been provided.
+///
+/// A header is an optional block of TOML at the very start of a template, opened by a
+/// `--- dn` line and closed by a `---` line, in which the template declares its own settings.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the template cannot be resolved or read, or if its header is
+/// invalid.
+///
+/// # Example
+///
+/// ```
+/// let template = read_template(&Config::default())?;
+/// assert!(template.is_none());
+/// ```
+pub fn read_template(config: &Config) -> Result<Option<Template>, Error> {
+    let Some(NamedTemplate {
+        path,
+        executable,
+        fields,
+        ..
+    }) = resolve_template(config)?
+    else {
+        return Ok(None);
+    };
+
+    check_field_names(&fields)
+        .map_err(|e| e.context(format!("Invalid fields for template {}", path.display())))?;
+
+    // NOTE: Executable templates are often compiled programs, so they are run rather than read.
+    if executable {
+        return Ok(Some(Template {
+            path,
+            executable,
+            header: TemplateHeader {
+                fields,
+                ..Default::default()
+            },
+            body: Vec::new(),
+        }));
+    }
+
+    let content = fs::read(&path)
+        .map_err(|e| anyhow!(e).context(format!("Unable to read {}", path.display())))?;
+    let (mut header, body) = split_header(content)
+        .map_err(|e| e.context(format!("Invalid header in template {}", path.display())))?;
+
+    if let Some(field) = header
+        .fields
+        .iter()
+        .find(|f| fields.iter().any(|c| c.name == f.name))
+    {
+        return Err(anyhow!(
+            "Field {} of template {} is declared in both the configuration file and its header",
+            field.name,
+            path.display()
+        ));
+    }
+
+    header.fields.splice(0..0, fields);
+
+    Ok(Some(Template {
+        path,
+        executable,
+        header,
+        body,
+    }))
+}
+
+/// Separates a template's header from its body, returning a default header if it has none.
+fn split_header(content: Vec<u8>) -> Result<(TemplateHeader, Vec<u8>), Error> {
+    let Ok(text) = std::str::from_utf8(&content) else {
+        return Ok((TemplateHeader::default(), content));
+    };
+
+    let mut lines = text.split_inclusive('\n');
+
+    let Some(opening) = lines
+        .next()
+        .filter(|l| l.trim_end() == TEMPLATE_HEADER_OPEN)
+    else {
+        return Ok((TemplateHeader::default(), content));
+    };
+
+    let mut header = String::new();
+    let mut offset = opening.len();
+
+    for line in lines {
+        offset += line.len();
+
+        if line.trim_end() == TEMPLATE_HEADER_CLOSE {
+            let body = content.get(offset..).unwrap_or_default().to_vec();
+
+            let header = toml::from_str::<TemplateHeader>(&header)?;
+            check_field_names(&header.fields)?;
+
+            return Ok((header, body));
+        }
+
+        header.push_str(line);
+    }
+
+    Err(anyhow!(
+        "Header is not closed by a {TEMPLATE_HEADER_CLOSE} line"
+    ))
+}
+
+/// Checks that no field uses the name of a variable provided by dn.
+fn check_field_names(fields: &[TemplateField]) -> Result<(), Error> {
+    match fields
+        .iter()
+        .find(|f| RESERVED_VARIABLES.contains(&f.name.as_str()))
+    {
+        Some(field) => Err(anyhow!(
+            "Field name {} is reserved for a variable provided by dn",
+            field.name
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Determines the value of every field declared by a template.
+///
+/// Values are taken from `key=value` assignments first. Any remaining fields are prompted for
+/// when `interactive` is set, and otherwise take their default values.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if an assignment is malformed or refers to a field which the
+/// template does not declare, if prompting fails, or if a field has no value.
+///
+/// # Example
+///
+/// ```
+/// let fields = collect_fields(template.as_ref(), &["mood=calm".to_owned()], false)?;
+/// assert_eq!(fields.get("mood"), Some(&"calm".to_owned()));
+/// ```
+pub fn collect_fields(
+    template: Option<&Template>,
+    assignments: &[String],
+    interactive: bool,
+) -> Result<BTreeMap<String, String>, Error> {
+    let declared = template
+        .map(|t| t.header.fields.as_slice())
+        .unwrap_or_default();
+    let mut assigned = BTreeMap::new();
+
+    for assignment in assignments {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid field assignment {assignment}: Expected NAME=VALUE"))?;
+
+        if !declared.iter().any(|f| f.name == name) {
+            return Err(anyhow!(
+                "The template does not declare a field named {name}"
+            ));
+        }
+
+        assigned.insert(name.to_owned(), value.to_owned());
+    }
+
+    let mut fields = BTreeMap::new();
+
+    for field in declared {
+        let value = match assigned.remove(&field.name) {
+            Some(value) => value,
+            None if interactive => prompt(
+                field.prompt.as_deref().unwrap_or(&field.name),
+                field.default.as_deref().unwrap_or_default(),
+                &[],
+            )?,
+            None => field.default.clone().ok_or_else(|| {
+                anyhow!(
+                    "No value for template field {0}: Use --set {0}=VALUE",
+                    field.name
+                )
+            })?,
+        };
+
+        fields.insert(field.name.clone(), value);
+    }
+
+    Ok(fields)
+}
+
+/// Substitutes the field placeholders of a metadata value declared in a template header, such
+/// as its `title`, returning the value as it should be passed to the metadata builder.
+pub fn render_header_value(
+    value: &str,
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> String {
+    let rendered = substitute_variables(value.as_bytes(), metadata, config, fields);
+
+    String::from_utf8_lossy(&rendered.content).into_owned()
+}
+
+/// The content of a template after its variables have been substituted.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderedTemplate {
+    /// The rendered content.
+    pub content: Vec<u8>,
+    /// The placeholders which were not recognised and have been left unchanged.
+    pub unrecognised: Vec<String>,
+}
+
+/// Renders a template for a new note with the language it is written in. Renders nothing when
+/// no template has been provided.
+///
+/// # Errors
+///
+/// Returns an `anyhow::Error` if the template cannot be rendered.
+///
+/// # Example
+///
+/// ```
+/// let template = read_template(&config)?;
+/// let rendered = render_template(template.as_ref(), &metadata, &config, &fields)?;
+/// ```
+pub fn render_template(
+    template: Option<&Template>,
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> Result<RenderedTemplate, Error> {
+    match template {
+        None => Ok(RenderedTemplate {
+            content: Vec::new(),
+            unrecognised: Vec::new(),
+        }),
+        Some(t) => match t.engine() {
+            TemplateEngine::Simple => Ok(substitute_variables(&t.body, metadata, config, fields)),
+            TemplateEngine::Jinja => render_jinja(t, metadata, config, fields),
+            TemplateEngine::Executable => run_executable(t, metadata, config, fields),
+        },
+    }
+}
+
+/// Substitutes the `{{variable}}` placeholders of a template with values from the note's
+/// metadata, the creation date, and the environment.
+///
+/// The supported variables are `identifier`, `title` (as provided), `title_slug` (as it appears
+/// in the file name), `keywords` (separated by spaces), `signature`, `extension`, `date` (in the
+/// configured `template_date_format`), `date:FORMAT` (in any `chrono` format), and `env:NAME`.
+/// Absent metadata is substituted with an empty string.
+///
+/// Placeholders which are not recognised, or which refer to unset environment variables or
+/// invalid date formats, are left unchanged and reported. Templates which are not valid UTF-8
+/// are returned as they are.
+///
+/// # Example
+///
+/// ```
+/// let rendered = substitute_variables(b"# {{title}} ({{mood}})", &metadata, &config, &fields);
+/// assert_eq!(rendered.content, b"# My Note (Monday)".to_vec());
+/// ```
+fn substitute_variables(
+    template: &[u8],
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> RenderedTemplate {
+    let Ok(text) = std::str::from_utf8(template) else {
+        return RenderedTemplate {
+            content: template.to_vec(),
+            unrecognised: Vec::new(),
+        };
+    };
+
+    let mut content = String::with_capacity(text.len());
+    let mut unrecognised = Vec::new();
+    let mut last_end = 0;
+
+    for captures in REGEX_PLACEHOLDER.captures_iter(text) {
+        let (Some(whole), Some(variable)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+
+        content.push_str(text.get(last_end..whole.start()).unwrap_or_default());
+
+        if let Some(value) = variable_value(variable.as_str(), metadata, config, fields) {
+            content.push_str(&value);
+        } else {
+            content.push_str(whole.as_str());
+            unrecognised.push(whole.as_str().to_owned());
+        }
+
+        last_end = whole.end();
+    }
+
+    content.push_str(text.get(last_end..).unwrap_or_default());
+
+    RenderedTemplate {
+        content: content.into_bytes(),
+        unrecognised,
+    }
+}
+
+/// Renders a Jinja template, with the note's metadata and the configuration as its context.
+///
+/// Besides the variables available to plain templates, the context contains `keywords` as a
+/// list, `config`, and the functions `date_format(format)` and `env(name)`. Other templates can
+/// be included from the template directory, or from the template's own directory if none is
+/// configured.
+fn render_jinja(
+    template: &Template,
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> Result<RenderedTemplate, Error> {
+    let name = template
+        .path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("template")
+        .to_owned();
+    let source = String::from_utf8(template.body.clone())
+        .map_err(|e| anyhow!(e).context("Jinja templates must be valid UTF-8"))?;
+    let include_directory = match config.template_directory.as_ref() {
+        Some(directory) => expand_path(directory)?,
+        None => template
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+    };
+
+    let mut environment = Environment::new();
+    environment.set_undefined_behavior(UndefinedBehavior::Strict);
+    environment.set_keep_trailing_newline(true);
+    environment.set_loader(path_loader(include_directory));
+
+    let date_metadata = metadata.clone();
+    environment.add_function(
+        "date_format",
+        move |format: &str| -> Result<String, minijinja::Error> {
+            format_date(&date_metadata, format).ok_or_else(|| {
+                minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("invalid date format {format}"),
+                )
+            })
+        },
+    );
+    environment.add_function("env", |name: &str| env::var(name).unwrap_or_default());
+    environment.add_template_owned(name.clone(), source)?;
+
+    let content = environment.get_template(&name)?.render(context! {
+        identifier => metadata.identifier,
+        title => variable_value("title", metadata, config, fields).unwrap_or_default(),
+        title_slug => metadata.title.clone().unwrap_or_default(),
+        keywords => metadata.keywords.clone().unwrap_or_default(),
+        signature => metadata.signature.clone().unwrap_or_default(),
+        extension => metadata.extension,
+        date => format_date(metadata, &config.template_date_format).unwrap_or_default(),
+        config => Value::from_serialize(config),
+        ..Value::from_serialize(fields)
+    })?;
+
+    Ok(RenderedTemplate {
+        content: content.into_bytes(),
+        unrecognised: Vec::new(),
+    })
+}
+
+/// Runs an executable template and uses its standard output as the content of the note.
+///
+/// The note's metadata is provided both as `DN_`-prefixed environment variables and as a JSON
+/// object on standard input, while standard error is passed through to the terminal.
+fn run_executable(
+    template: &Template,
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> Result<RenderedTemplate, Error> {
+    let title = variable_value("title", metadata, config, fields).unwrap_or_default();
+    let date = format_date(metadata, &config.template_date_format).unwrap_or_default();
+    let keywords = metadata.keywords.clone().unwrap_or_default();
+    let input = serde_json::to_vec(&serde_json::json!({
+        "identifier": metadata.identifier,
+        "title": title,
+        "title_slug": metadata.title,
+        "keywords": keywords,
+        "signature": metadata.signature,
+        "extension": metadata.extension,
+        "date": date,
+        "directory": config.directory,
+        "fields": fields,
+    }))?;
+
+    let mut child = Command::new(&template.path)
+        .env("DN_IDENTIFIER", &metadata.identifier)
+        .env("DN_TITLE", &title)
+        .env(
+            "DN_TITLE_SLUG",
+            metadata.title.as_deref().unwrap_or_default(),
+        )
+        .env("DN_KEYWORDS", keywords.join(" "))
+        .env(
+            "DN_SIGNATURE",
+            metadata.signature.as_deref().unwrap_or_default(),
+        )
+        .env("DN_EXTENSION", &metadata.extension)
+        .env("DN_DATE", &date)
+        .env("DN_DIRECTORY", &config.directory)
+        .envs(
+            fields
+                .iter()
+                .map(|(name, value)| (format!("DN_FIELD_{}", name.to_uppercase()), value)),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow!(e).context(format!("Unable to run {}", template.path.display())))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // NOTE: Programs are free to ignore their input and exit before it has been written.
+        match stdin.write_all(&input) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(anyhow!(e)),
+            _ => {}
+        }
+    }
+
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed with {}",
+            template.path.display(),
+            output.status
+        ));
+    }
+
+    Ok(RenderedTemplate {
+        content: output.stdout,
+        unrecognised: Vec::new(),
+    })
+}
+
+/// Determines the value of a single template variable, returning `None` if it cannot be
+/// determined.
+fn variable_value(
+    variable: &str,
+    metadata: &FileMetadata,
+    config: &Config,
+    fields: &BTreeMap<String, String>,
+) -> Option<String> {
+    match variable.split_once(':') {
+        Some(("date", format)) => format_date(metadata, format),
+        Some(("env", name)) => env::var(name).ok(),
+        Some(_) => None,
+        None => match variable {
+            "identifier" => Some(metadata.identifier.clone()),
+            "title" => Some(
+                metadata
+                    .title_raw
+                    .clone()
+                    .or_else(|| metadata.title.clone())
+                    .unwrap_or_default(),
+            ),
+            "title_slug" => Some(metadata.title.clone().unwrap_or_default()),
+            "keywords" => Some(metadata.keywords.clone().unwrap_or_default().join(" ")),
+            "signature" => Some(metadata.signature.clone().unwrap_or_default()),
+            "extension" => Some(metadata.extension.clone()),
+            "date" => format_date(metadata, &config.template_date_format),
+            _ => fields.get(variable).cloned(),
+        },
+    }
+}
+
+/// Formats the creation date of a note, as given by its identifier, returning `None` if the
+/// format string is invalid.
+fn format_date(metadata: &FileMetadata, format: &str) -> Option<String> {
+    // NOTE: Formatting with an invalid format string panics, so it must be validated first.
+    if StrftimeItems::new(format).any(|i| matches!(i, Item::Error)) {
+        return None;
+    }
+
+    identifier_date(&metadata.identifier).map(|d| d.format(format).to_string())
+}
+
+///////////
+// Tests //
+///////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::TemplateRule, testing::TempDirectory};
+
+    #[test]
+    fn read_template_is_empty_without_template() {
+        // Arrange
+        let config = Config {
+            template_path: None,
+            ..Default::default()
+        };
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "No template is read")]
+        let result = read_template(&config).unwrap();
+
+        // Assert
+        assert!(result.is_none(), "Expected no template");
+    }
+
+    #[test]
+    fn read_template_separates_header() {
+        // Arrange
+        let directory = TempDirectory::new("template");
+        let path = directory.write(
+            "template.txt",
+            "--- dn\nengine = \"jinja\"\n---\n# Journal\n",
+        );
+        let config = Config {
+            template_path: Some(path),
+            ..Default::default()
+        };
+
+        // Act
+        let result = read_template(&config);
+
+        // Assert
+        #[allow(clippy::unwrap_used, reason = "The template was just written")]
+        let template = result.unwrap().unwrap();
+        assert_eq!(b"# Journal\n".to_vec(), template.body);
+        assert_eq!(TemplateEngine::Jinja, template.engine());
+    }
+
+    #[test]
+    fn read_template_takes_fields_from_configuration() {
+        // Arrange
+        let directory = TempDirectory::new("configured-fields");
+        let path = directory.write("weekly.sh", "#!/bin/sh\n");
+        let field = TemplateField {
+            name: "project".to_owned(),
+            prompt: None,
+            default: Some("dn".to_owned()),
+        };
+        let config = Config {
+            template_path: Some(PathBuf::from("weekly")),
+            templates: BTreeMap::from([(
+                "weekly".to_owned(),
+                TemplateEntry::Detailed {
+                    path,
+                    executable: true,
+                    fields: vec![field.clone()],
+                },
+            )]),
+            ..Default::default()
+        };
+
+        // Act
+        #[allow(clippy::unwrap_used, reason = "The template was just written")]
+        let template = read_template(&config).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(vec![field], template.header.fields);
+    }
+
+    #[test]
+    fn split_header_rejects_executable_engine() {
+        // Arrange
+        let input = b"--- dn\nengine = \"executable\"\n---\n#!/bin/sh\n".to_vec();
+
+        // Act
+        let result = split_header(input);
+
+        // Assert
+        assert!(
+            result.is_err(),
+            "Expected executable templates to be rejected in headers"
+        );
+    }
+
+    #[test]
+    fn resolve_template_prefers_names_over_paths() {
+        // Arrange
+        let directory = TempDirectory::new("templates");
+        let journal = directory.write("journal.md", "journal");
+        let meeting = directory.write("meeting.txt", "meeting");
+        let base = Config {
+            template_directory: Some(directory.path().to_path_buf()),
+            templates: BTreeMap::from([(
+                "standup".to_owned(),
+                TemplateEntry::Path(PathBuf::from("meeting.txt")),
+            )]),
+            ..Default::default()
+        };
+        let test_cases = [("journal", journal), ("standup", meeting)];
+
+        for (input, expected) in test_cases {
+            let config = Config {
+                template_path: Some(PathBuf::from(input)),
+                ..base.clone()
+            };
+
+            // Act
+            let result = resolve_template(&config);
+
+            // Assert
+            #[allow(clippy::unwrap_used, reason = "The templates were just written")]
+            let path = result.unwrap().map(|t| t.path);
+            assert_eq!(Some(expected), path, "Template: {input}");
+        }
+    }
+
+    #[test]
+    fn render_template_substitutes_variables() {
+        // Arrange
+        let config = Config::default();
+        let metadata = FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            signature: Some("1a".to_owned()),
+            title: Some("my-note".to_owned()),
+            title_raw: Some("My Note!".to_owned()),
+            keywords: Some(vec!["a".to_owned(), "b".to_owned()]),
+            extension: "md".to_owned(),
+        };
+        let fields = BTreeMap::from([("mood".to_owned(), "calm".to_owned())]);
+        let input = "# {{title}} ({{ title_slug }})\n{{identifier}} {{signature}} {{keywords}} \
+                     {{extension}} {{date}} {{date:%A %H:%M}} {{mood}} {{unknown}} {{date:%Q}}"
+            .as_bytes()
+            .to_vec();
+        let expected = RenderedTemplate {
+            content: "# My Note! (my-note)\n20240101T120000 1a a b \
+                      md 2024-01-01 Monday 12:00 calm {{unknown}} {{date:%Q}}"
+                .as_bytes()
+                .to_vec(),
+            unrecognised: vec!["{{unknown}}".to_owned(), "{{date:%Q}}".to_owned()],
+        };
+
+        // Act
+        let result = substitute_variables(&input, &metadata, &config, &fields);
+
+        // Assert
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn render_template_renders_jinja() {
+        // Arrange
+        let directory = TempDirectory::new("jinja");
+        directory.write("retro.md", "## Retro\n");
+        let config = Config {
+            template_directory: Some(directory.path().to_path_buf()),
+            ..Default::default()
+        };
+        let metadata = FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            title: Some("standup".to_owned()),
+            title_raw: Some("Standup".to_owned()),
+            keywords: Some(vec!["retro".to_owned(), "work".to_owned()]),
+            extension: "md".to_owned(),
+            ..Default::default()
+        };
+        let template = Template {
+            path: directory.path().join("meeting.md.jinja"),
+            executable: false,
+            header: TemplateHeader::default(),
+            body: "# {{ title }} ({{ date_format('%A') }})\n\
+                   {% for k in keywords %}#{{ k }} {% endfor %}\n\
+                   {% if 'retro' in keywords %}{% include 'retro.md' %}{% endif %}"
+                .as_bytes()
+                .to_vec(),
+        };
+        let expected = "# Standup (Monday)\n#retro #work \n## Retro\n"
+            .as_bytes()
+            .to_vec();
+
+        // Act
+        let result = render_template(Some(&template), &metadata, &config, &BTreeMap::new());
+
+        // Assert
+        #[allow(clippy::unwrap_used, reason = "The template is valid")]
+        let rendered = result.unwrap();
+        assert_eq!(expected, rendered.content);
+    }
+
+    #[test]
+    fn render_template_renders_missing_jinja_values_as_empty() {
+        // Arrange
+        let config = Config::default();
+        let metadata = FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            extension: "md".to_owned(),
+            ..Default::default()
+        };
+        let template = Template {
+            path: PathBuf::from("note.md.jinja"),
+            executable: false,
+            header: TemplateHeader::default(),
+            body: "[{{ signature }}][{{ title_slug }}][{{ env('DN_TEST_UNSET_VARIABLE') }}]"
+                .as_bytes()
+                .to_vec(),
+        };
+        let expected = b"[][][]".to_vec();
+
+        // Act
+        let result = render_template(Some(&template), &metadata, &config, &BTreeMap::new());
+
+        // Assert
+        #[allow(clippy::unwrap_used, reason = "The template is valid")]
+        let rendered = result.unwrap();
+        assert_eq!(expected, rendered.content);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn render_template_runs_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Arrange
+        let directory = TempDirectory::new("executable");
+        let succeeding = directory.write("succeeding.sh", "#!/bin/sh\necho \"# $DN_TITLE\"\ncat\n");
+        let failing = directory.write("failing.sh", "#!/bin/sh\nexit 3\n");
+
+        for path in [&succeeding, &failing] {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+                .expect("Unable to make test program executable");
+        }
+
+        let config = Config::default();
+        let metadata = FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            title: Some("weekly".to_owned()),
+            title_raw: Some("Weekly".to_owned()),
+            extension: "md".to_owned(),
+            ..Default::default()
+        };
+        let template = |path: &Path| Template {
+            path: path.to_path_buf(),
+            executable: true,
+            header: TemplateHeader::default(),
+            body: Vec::new(),
+        };
+
+        // Act
+        let fields = BTreeMap::new();
+        let succeeded = render_template(Some(&template(&succeeding)), &metadata, &config, &fields);
+        let failed = render_template(Some(&template(&failing)), &metadata, &config, &fields);
+
+        // Assert
+        #[allow(clippy::unwrap_used, reason = "The program succeeds")]
+        let content = String::from_utf8(succeeded.unwrap().content).unwrap_or_default();
+        assert!(
+            content.starts_with("# Weekly\n{") && content.contains("\"title\":\"Weekly\""),
+            "Unexpected output: {content}"
+        );
+        assert!(failed.is_err(), "Expected a failing program to be an error");
+    }
+
+    #[test]
+    fn select_template_selects_first_matching_rule() {
+        // Arrange
+        let rule = |template: &str| TemplateRule {
+            template: PathBuf::from(template),
+            keywords: None,
+            extension: None,
+            signature: None,
+            directory: None,
+        };
+        let config = Config {
+            template_rules: vec![
+                TemplateRule {
+                    keywords: Some(vec!["Meeting".to_owned(), "work".to_owned()]),
+                    ..rule("work-meeting")
+                },
+                TemplateRule {
+                    extension: Some(".org".to_owned()),
+                    ..rule("org")
+                },
+                TemplateRule {
+                    directory: Some(PathBuf::from("/notes/journal")),
+                    ..rule("journal")
+                },
+            ],
+            ..Default::default()
+        };
+        let metadata = |keywords: &[&str], extension: &str| FileMetadata {
+            identifier: "20240101T120000".to_owned(),
+            keywords: Some(keywords.iter().map(|k| (*k).to_owned()).collect()),
+            extension: extension.to_owned(),
+            ..Default::default()
+        };
+        let test_cases = [
+            (
+                metadata(&["meeting", "work"], "org"),
+                "/notes",
+                Some("work-meeting"),
+            ),
+            (metadata(&["meeting"], "org"), "/notes", Some("org")),
+            (metadata(&[], "md"), "/notes/journal/2024", Some("journal")),
+            (metadata(&["meeting"], "md"), "/notes", None),
+        ];
+
+        for (input, directory, expected) in test_cases {
+            // Act
+            #[allow(clippy::unwrap_used, reason = "The directories are absolute")]
+            let result = select_template(&config, &input, Path::new(directory)).unwrap();
+
+            // Assert
+            assert_eq!(expected.map(PathBuf::from), result, "Metadata: {input:?}");
+        }
+    }
+
+    #[test]
+    fn collect_fields_uses_assignments_and_defaults() {
+        // Arrange
+        let field = |name: &str, default: Option<&str>| TemplateField {
+            name: name.to_owned(),
+            prompt: None,
+            default: default.map(String::from),
+        };
+        let template = Template {
+            path: PathBuf::from("meeting.md"),
+            executable: false,
+            header: TemplateHeader {
+                fields: vec![field("attendees", None), field("mood", Some("calm"))],
+                ..Default::default()
+            },
+            body: Vec::new(),
+        };
+        let expected = BTreeMap::from([
+            ("attendees".to_owned(), "Ada, Grace".to_owned()),
+            ("mood".to_owned(), "calm".to_owned()),
+        ]);
+
+        // Act
+        let result = collect_fields(Some(&template), &["attendees=Ada, Grace".to_owned()], false);
+        let missing = collect_fields(Some(&template), &[], false);
+        let undeclared = collect_fields(Some(&template), &["project=dn".to_owned()], false);
+
+        // Assert
+        #[allow(clippy::unwrap_used, reason = "Every field has a value")]
+        let fields = result.unwrap();
+        assert_eq!(expected, fields);
+        assert!(missing.is_err(), "Expected a missing field error");
+        assert!(undeclared.is_err(), "Expected an undeclared field error");
+    }
+
+    #[test]
+    fn resolve_template_rejects_missing_template() {
+        // Arrange
+        let config = Config {
+            template_path: Some(Path::new("/nonexistent/dn/template.txt").to_path_buf()),
+            ..Default::default()
+        };
+
+        // Act
+        let result = resolve_template(&config);
+
+        // Assert
+        assert!(result.is_err(), "Expected a missing template error");
+    }
+}
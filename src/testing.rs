@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: 2025 Matthew Mark Ibbetson
+// SPDX-FileContributor: Matthew Mark Ibbetson
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Fixtures shared by the tests of several modules.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// A temporary directory for a single test, which is removed along with its contents when it is
+/// dropped, including when the test fails.
+#[derive(Debug)]
+pub struct TempDirectory {
+    path: PathBuf,
+}
+
+impl TempDirectory {
+    /// Creates an empty directory with a name unique to the test process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory cannot be created, so that the test fails during setup.
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("dn-test-{}-{name}", process::id()));
+
+        if path.exists() {
+            fs::remove_dir_all(&path).expect("Unable to remove stale test directory");
+        }
+
+        fs::create_dir_all(&path).expect("Unable to create test directory");
+
+        Self { path }
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes a file at a path relative to the directory, creating any missing parent
+    /// directories, and returns its full path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be written, so that the test fails during setup.
+    pub fn write<C: AsRef<[u8]>>(&self, name: &str, content: C) -> PathBuf {
+        let path = self.path.join(name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Unable to create test subdirectory");
+        }
+
+        fs::write(&path, content).expect("Unable to write test file");
+
+        path
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        // NOTE: Cleanup failures cannot be reported from a destructor without aborting the test.
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}